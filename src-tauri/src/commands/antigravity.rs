@@ -38,12 +38,16 @@ use crate::core::oauth;
 use crate::core::oauth_server;
 use crate::core::quota;
 use crate::utils::paths;
-use tauri::AppHandle; 
+use tauri::{AppHandle, State};
+use crate::commands::AppState;
+use crate::core::error::AppError;
+use crate::core::platform::PlatformData;
 
 // ... keep existing structs ...
 
 /// 准备 OAuth URL
 #[command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn antigravity_prepare_oauth_url(app: AppHandle) -> Result<String, String> {
     // 使用自动服务器，启动本地监听并在 localhost 接收回调
     oauth_server::ensure_oauth_flow_prepared(Some(app)).await
@@ -51,6 +55,7 @@ pub async fn antigravity_prepare_oauth_url(app: AppHandle) -> Result<String, Str
 
 /// 完成 OAuth 登录
 #[command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn antigravity_complete_oauth(app: AppHandle, code: String) -> Result<AntigravityAccountData, String> {
     // 如果 code 为空，说明是自动回调，直接完成流程
     // 如果 code 不为空，说明是手动输入，需要先提交
@@ -75,6 +80,7 @@ pub async fn antigravity_complete_oauth(app: AppHandle, code: String) -> Result<
 
 /// 通过 Refresh Token 添加账号
 #[command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn antigravity_add_by_token(refresh_token: String) -> Result<AntigravityAccountData, String> {
     // 简单验证格式
     if !refresh_token.starts_with("1//") && !refresh_token.contains(".") {
@@ -105,6 +111,7 @@ pub struct TokenRefreshResponse {
 }
 
 #[command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn antigravity_refresh_token(refresh_token: String) -> Result<TokenRefreshResponse, String> {
     let token_res = oauth::refresh_access_token(&refresh_token).await?;
     
@@ -117,6 +124,7 @@ pub async fn antigravity_refresh_token(refresh_token: String) -> Result<TokenRef
 
 /// 获取配额信息
 #[command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn antigravity_get_quota(access_token: String) -> Result<quota::QuotaData, String> {
     let (data, _err) = quota::fetch_quota(&access_token, None).await?;
     Ok(data)
@@ -267,6 +275,7 @@ fn find_protobuf_field(data: &[u8], target_field: u32) -> Result<Option<Vec<u8>>
 /// 注意：此功能需要 tauri-plugin-dialog，当前返回 None 让前端使用手动输入
 /// 选择数据库文件
 #[command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn select_db_file(app: AppHandle) -> Result<Option<String>, String> {
     use tauri_plugin_dialog::DialogExt;
     
@@ -286,12 +295,18 @@ pub async fn select_db_file(app: AppHandle) -> Result<Option<String>, String> {
 /// 3. 关闭 Antigravity IDE 进程
 /// 4. 注入 Token 到数据库
 /// 5. 重启 Antigravity IDE
+/// 6. 在存储中标记为激活账号，并保存刷新后的 Token
 #[command]
+#[tracing::instrument(target = "applog", skip_all, fields(platform = "antigravity", account_id = %account_id))]
 pub async fn antigravity_switch_account(
+    app: AppHandle,
+    state: State<'_, AppState>,
     account_id: String,
     refresh_token: String,
     email: String,
-) -> Result<TokenRefreshResponse, String> {
+) -> Result<TokenRefreshResponse, AppError> {
+    state.ensure_unlocked()?;
+    
     log_info(&format!("Switching account: {} ({})", email, &account_id[..8]));
     
     // 1. 刷新 Token 确保有效
@@ -330,7 +345,16 @@ pub async fn antigravity_switch_account(
     
     log_info("Account switch completed");
     
-    // 返回新的 Token 信息，前端会更新账号状态
+    // 6. 标记为激活账号，并保存刷新后的 Token
+    state.record_switch(&app, &account_id, |account| {
+        if let PlatformData::Antigravity(data) = &mut account.platform_data {
+            data.token.access_token = token_res.access_token.clone();
+            data.token.expires_in = Some(token_res.expires_in);
+            data.token.expiry_timestamp = Some(expiry_timestamp);
+        }
+    });
+    
+    // 返回新的 Token 信息
     Ok(TokenRefreshResponse {
         access_token: token_res.access_token,
         expires_in: token_res.expires_in,
@@ -347,10 +371,7 @@ pub fn get_antigravity_version() -> Option<String> {
     log_debug("Checking Antigravity version");
     
     // 检查是否配置了 Antigravity 可执行文件
-    let exe_path = match crate::utils::config::load_app_config() {
-        Ok(config) => config.antigravity_executable?,
-        Err(_) => return None,
-    };
+    let exe_path = crate::core::settings::load().antigravity_executable?;
     
     // 尝试执行 antigravity --version
     #[cfg(target_os = "windows")]
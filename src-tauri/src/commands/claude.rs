@@ -1,8 +1,11 @@
+use crate::commands::AppState;
+use crate::core::error::AppError;
 use crate::utils::logger::log_info;
 use serde_json::{json, Value};
 use std::env;
 use std::fs;
 use std::path::PathBuf;
+use tauri::{AppHandle, State};
 
 /// Get Claude config file path
 /// Can be overridden by CLAUDE_CONFIG_PATH environment variable
@@ -32,12 +35,19 @@ fn write_atomic(path: &PathBuf, content: &str) -> Result<(), String> {
 }
 
 /// Switch Claude account by updating environment variables and config file
+///
+/// When `account_id` is given, the account is marked active in storage afterwards
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all, fields(platform = "claude", account_id = account_id.as_deref()))]
 pub async fn switch_claude_account(
+    app: AppHandle,
+    state: State<'_, AppState>,
     settings: Option<String>,
-) -> Result<(), String> {
+    account_id: Option<String>,
+) -> Result<(), AppError> {
     use crate::utils::logger::log_warn;
     
+    state.ensure_unlocked()?;
     log_info("Switching Claude account...");
 
     // Update Claude config file
@@ -72,7 +82,7 @@ pub async fn switch_claude_account(
         serde_json::from_str(&settings_str)
             .map_err(|e| format!("Failed to parse settings JSON: {}", e))?
     } else {
-        return Err("Settings parameter is required".to_string());
+        return Err("Settings parameter is required".into());
     };
 
     // Write config to file (atomic)
@@ -83,11 +93,15 @@ pub async fn switch_claude_account(
     
     log_info(&format!("Successfully updated Claude config at: {}", config_path.display()));
 
+    if let Some(id) = account_id {
+        state.record_switch(&app, &id, |_| {});
+    }
     Ok(())
 }
 
 /// Get current Claude configuration
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn get_claude_config() -> Result<Value, String> {
     let config_path = get_claude_config_path()?;
     
@@ -106,6 +120,7 @@ pub async fn get_claude_config() -> Result<Value, String> {
 
 /// Verify Claude API Key
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn verify_claude_api_key(api_key: String, base_url: String) -> Result<Value, String> {
     use crate::utils::logger::log_info;
 
@@ -2,7 +2,9 @@ use crate::utils::logger::{log_info, log_error};
 use serde_json::{json, Value};
 use std::fs;
 use std::path::PathBuf;
-use tauri::AppHandle;
+use tauri::{AppHandle, State};
+use crate::commands::AppState;
+use crate::core::error::AppError;
 
 fn get_codex_config_dir(_app: &AppHandle) -> Result<PathBuf, String> {
     let home_dir = dirs::home_dir()
@@ -159,11 +161,17 @@ fn write_codex_config_atomic(
 }
 
 /// Switch Codex account by updating active status and config files
+///
+/// When `account_id` is given, the account is marked active in storage afterwards
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all, fields(platform = "codex", account_id = account_id.as_deref()))]
 pub async fn switch_codex_account(
     app: AppHandle,
+    state: State<'_, AppState>,
     settings: Option<String>,
-) -> Result<(), String> {
+    account_id: Option<String>,
+) -> Result<(), AppError> {
+    state.ensure_unlocked()?;
     log_info("Switching Codex account...");
 
     // Parse settings JSON
@@ -183,12 +191,17 @@ pub async fn switch_codex_account(
     // Write both files atomically
     write_codex_config_atomic(&app, auth, config)?;
 
+    if let Some(id) = account_id {
+        state.record_switch(&app, &id, |_| {});
+    }
+
     log_info("Codex account switched successfully");
     Ok(())
 }
 
 /// Get current Codex configuration
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn get_codex_config(app: AppHandle) -> Result<Value, String> {
     let mut result = serde_json::Map::new();
     
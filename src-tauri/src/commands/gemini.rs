@@ -3,7 +3,9 @@ use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use tauri::AppHandle;
+use tauri::{AppHandle, State};
+use crate::commands::AppState;
+use crate::core::error::AppError;
 
 /// Get Gemini config directory
 fn get_gemini_config_dir(_app: &AppHandle) -> Result<PathBuf, String> {
@@ -230,11 +232,17 @@ fn write_gemini_config_atomic(
 }
 
 /// Switch Gemini account by updating config files with settings
+///
+/// When `account_id` is given, the account is marked active in storage afterwards
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all, fields(platform = "gemini", account_id = account_id.as_deref()))]
 pub async fn switch_gemini_account(
     app: AppHandle,
+    state: State<'_, AppState>,
     settings: Option<String>,
-) -> Result<(), String> {
+    account_id: Option<String>,
+) -> Result<(), AppError> {
+    state.ensure_unlocked()?;
     log_info("Switching Gemini account...");
 
     // Parse settings JSON
@@ -257,12 +265,17 @@ pub async fn switch_gemini_account(
     // Write all files atomically
     write_gemini_config_atomic(&app, env, config, settings)?;
 
+    if let Some(id) = account_id {
+        state.record_switch(&app, &id, |_| {});
+    }
+
     log_info("Gemini account switched successfully");
     Ok(())
 }
 
 /// Get current Gemini configuration
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn get_gemini_config(app: AppHandle) -> Result<Value, String> {
     let mut result = serde_json::Map::new();
     
@@ -1,5 +1,7 @@
 use crate::core::kiro as core_kiro;
-use tauri::{command, AppHandle, Manager};
+use tauri::{command, AppHandle, Manager, State};
+use crate::commands::AppState;
+use crate::core::error::AppError;
 use serde::Serialize;
 use tauri_plugin_opener::OpenerExt;
 use crate::utils::common::{generate_account_id, extract_username_from_email};
@@ -41,6 +43,7 @@ pub struct KiroAccount {
 
 /// 启动设备授权流程
 #[command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn kiro_start_device_auth() -> Result<DeviceAuthResult, String> {
     // 1. 注册客户端
     let (client_id, client_secret) = core_kiro::register_client().await?;
@@ -69,6 +72,7 @@ pub async fn kiro_start_device_auth() -> Result<DeviceAuthResult, String> {
 
 /// 轮询 Token 并获取完整账号信息
 #[command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn kiro_poll_token(
     device_code: String, 
     client_id: String, 
@@ -120,12 +124,14 @@ pub async fn kiro_poll_token(
 
 /// 检查配额
 #[command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn kiro_check_quota(access_token: String) -> Result<core_kiro::KiroQuotaData, String> {
     core_kiro::get_usage_limits(&access_token).await
 }
 
 /// 刷新 Token
 #[command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn kiro_refresh_token(
     refresh_token: String, 
     client_id: String, 
@@ -142,6 +148,7 @@ pub fn kiro_cancel_auth() -> Result<(), String> {
 
 /// 导入 SSO Token
 #[command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn kiro_import_sso_token(token: String) -> Result<KiroAccount, String> {
     // 验证 Token (通过获取配额)
     let quota_res = core_kiro::get_usage_limits(&token).await
@@ -165,10 +172,14 @@ pub async fn kiro_import_sso_token(token: String) -> Result<KiroAccount, String>
 
 /// 验证 OIDC 凭证并导入
 #[command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn kiro_verify_credentials(credentials: serde_json::Value) -> Result<KiroAccount, String> {
     use crate::utils::logger::log_info;
     
-    log_info(&format!("[OIDC Import] Received credentials: {}", serde_json::to_string(&credentials).unwrap_or_default()));
+    let fields: Vec<&str> = credentials.as_object()
+        .map(|map| map.keys().map(String::as_str).collect())
+        .unwrap_or_default();
+    log_info(format!("[OIDC Import] Received credentials with fields: {:?}", fields));
     
     // 支持 camelCase 和 snake_case 两种格式
     let client_id = credentials.get("clientId")
@@ -239,6 +250,7 @@ pub async fn kiro_verify_credentials(credentials: serde_json::Value) -> Result<K
 
 /// 社交登录
 #[command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn kiro_social_login(app: AppHandle, provider: String) -> Result<KiroAccount, String> {
     let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(1);
     
@@ -284,8 +296,13 @@ pub async fn kiro_social_login(app: AppHandle, provider: String) -> Result<KiroA
 }
 
 /// 切换 Kiro 账号 - 写入凭证到本地 SSO 缓存
+///
+/// 传入 account_id 时，写入成功后在存储中将该账号标记为激活
 #[command]
+#[tracing::instrument(target = "applog", skip_all, fields(platform = "kiro", account_id = account_id.as_deref()))]
 pub async fn switch_kiro_account(
+    app: AppHandle,
+    state: State<'_, AppState>,
     access_token: String,
     refresh_token: String,
     client_id: String,
@@ -293,11 +310,14 @@ pub async fn switch_kiro_account(
     region: Option<String>,
     start_url: Option<String>,
     auth_method: Option<String>,
-    provider: Option<String>
-) -> Result<(), String> {
+    provider: Option<String>,
+    account_id: Option<String>,
+) -> Result<(), AppError> {
     use crate::utils::logger::log_info;
     use sha1::{Sha1, Digest};
     
+    state.ensure_unlocked()?;
+    
     log_info("[Switch Account] Starting account switch...");
     
     let region = region.unwrap_or_else(|| "us-east-1".to_string());
@@ -364,12 +384,17 @@ pub async fn switch_kiro_account(
         log_info(&format!("[Switch Account] Client registration saved to: {}", client_reg_path.display()));
     }
     
+    if let Some(id) = account_id {
+        state.record_switch(&app, &id, |_| {});
+    }
+    
     log_info("[Switch Account] Account switch completed successfully");
     Ok(())
 }
 
 /// 在隐私模式下打开浏览器
 #[command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn open_url_in_private_mode(app: AppHandle, url: String) -> Result<(), String> {
     use crate::utils::logger::{log_info, log_error};
     use tauri_plugin_shell::ShellExt;
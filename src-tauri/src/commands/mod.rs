@@ -1,6 +1,15 @@
 use crate::core::{Account, Storage};
+use crate::core::error::AppError;
+use crate::core::merge::{merge_accounts, ImportReport, MergeStrategy};
+use crate::core::export::{build_export, open_bundle, parse_import, seal_bundle, ExportOptions};
+use crate::core::query::AccountQuery;
+use crate::core::audit::{self, diff_accounts, AuditAction, AuditEntry};
+use crate::core::{secret_store, watcher};
+use crate::utils::log_query::{self, LogEntry, LogQuery};
+use crate::utils::logger::{log_info, log_warn};
 use tauri::{AppHandle, State};
-use std::sync::Mutex;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
 pub mod import;
 pub mod machine;
 pub mod antigravity;
@@ -9,33 +18,133 @@ pub mod claude;
 pub mod codex;
 pub mod gemini;
 pub mod provider;
+pub mod vault;
+pub mod trash;
+pub mod switching;
 
 pub struct AppState {
+    app: AppHandle,
     pub storage: Mutex<Storage>,
+    /// 最近一次访问账户数据的时间，用于空闲自动锁定
+    pub last_activity: Mutex<Instant>,
+}
+
+impl AppState {
+    pub fn new(app: AppHandle, storage: Storage) -> Self {
+        Self {
+            app,
+            storage: Mutex::new(storage),
+            last_activity: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// 获取账户存储；Vault 锁定时返回 Locked 错误，并刷新空闲计时
+    ///
+    /// 返回前先合并存储文件的外部修改，避免随后的保存覆盖它们
+    pub fn storage(&self) -> Result<MutexGuard<'_, Storage>, AppError> {
+        let mut storage = self.storage.lock().unwrap();
+        if storage.locked {
+            return Err(AppError::locked());
+        }
+        // 密钥后端之前不可用时重试，成功后恢复可写
+        if storage.secrets_error.is_some() {
+            secret_store::resolve(&mut storage);
+        }
+        watcher::sync_external_changes(&self.app, &mut storage);
+        self.touch();
+        Ok(storage)
+    }
+
+    /// 确认 Vault 未锁定（用于不直接读写存储的命令，如账号切换）
+    pub fn ensure_unlocked(&self) -> Result<(), AppError> {
+        self.storage().map(|_| ())
+    }
+
+    /// 账号切换成功后由后端标记激活账户并记录切换历史
+    ///
+    /// update 用于在同一次保存中写入切换时获得的新数据（如刷新后的 Token）。
+    /// 此时切换已经完成，记录失败（如账户不在存储中）只写日志，不让命令报告切换失败
+    pub fn record_switch(&self, app: &AppHandle, account_id: &str, update: impl FnOnce(&mut Account)) {
+        if let Err(e) = self.try_record_switch(app, account_id, update) {
+            log_warn(format!("Account {} switched but the switch could not be recorded: {}", account_id, e.message));
+        }
+    }
+
+    fn try_record_switch(
+        &self,
+        app: &AppHandle,
+        account_id: &str,
+        update: impl FnOnce(&mut Account),
+    ) -> Result<(), AppError> {
+        let mut storage = self.storage()?;
+        let mut switched = storage.clone();
+        if let Some(account) = switched.accounts.iter_mut().find(|a| a.id == account_id) {
+            let before = account.platform_data.clone();
+            update(account);
+            if account.platform_data != before {
+                account.updated_at = chrono::Utc::now().timestamp_millis();
+            }
+        }
+        let record = switched.activate(account_id)?;
+
+        switched.save(app)?;
+        audit::record(app, &diff_accounts(AuditAction::Switch, &storage.accounts, &switched.accounts));
+        *storage = switched;
+
+        log_info(format!("Switched {} account to {} (previous: {:?})", record.platform, record.account_id, record.previous_id));
+        Ok(())
+    }
+
+    /// 记录一次用户活动
+    pub fn touch(&self) {
+        if let Ok(mut last) = self.last_activity.lock() {
+            *last = Instant::now();
+        }
+    }
 }
 
 #[tauri::command]
-pub fn get_accounts(_app: AppHandle, state: State<AppState>) -> Result<Vec<Account>, String> {
-    let storage = state.storage.lock().unwrap();
+pub fn get_accounts(_app: AppHandle, state: State<AppState>) -> Result<Vec<Account>, AppError> {
+    let storage = state.storage()?;
     Ok(storage.accounts.clone())
 }
 
+/// 按平台、标签、分组、激活状态、额度状态和邮箱筛选账户，并按任意字段排序
+#[tauri::command]
+pub fn query_accounts(
+    state: State<AppState>,
+    query: Option<AccountQuery>,
+) -> Result<Vec<Account>, AppError> {
+    let storage = state.storage()?;
+    Ok(query.unwrap_or_default().run(&storage.accounts))
+}
+
 #[tauri::command]
 pub fn add_account(
     app: AppHandle,
     state: State<AppState>,
     account: Account,
-) -> Result<Account, String> {
+) -> Result<Account, AppError> {
     use crate::utils::logger::log_info;
     
-    log_info(&format!("[Storage] Adding account: {}", account.email));
-    log_info(&format!("[Storage] Platform data: {}", serde_json::to_string_pretty(&account.platform_data).unwrap_or_default()));
+    log_info(format!("[Storage] Adding {} account: {}", account.platform, account.email));
     
-    let mut storage = state.storage.lock().unwrap();
+    account.validate()?;
+    
+    let account = Account { updated_at: chrono::Utc::now().timestamp_millis(), ..account };
+    let mut storage = state.storage()?;
+    if storage.accounts.iter().any(|a| a.id == account.id) {
+        return Err(format!("Account id {} already exists", account.id).into());
+    }
     storage.accounts.push(account.clone());
-    storage.save(&app)?;
+    if let Err(e) = storage.save_account(&app, &account) {
+        // 保存失败（如存储冲突）时撤销，否则下次访问会把它合并写入，重试时产生重复 id
+        storage.accounts.pop();
+        return Err(e.into());
+    }
     
     log_info("[Storage] Account saved successfully");
+    audit::record(&app, &[AuditEntry::new(AuditAction::Add, None, Some(&account))]);
     Ok(account)
 }
 
@@ -45,15 +154,31 @@ pub fn update_account(
     state: State<AppState>,
     id: String,
     account: Account,
-) -> Result<Account, String> {
-    let mut storage = state.storage.lock().unwrap();
+) -> Result<Account, AppError> {
+    account.validate()?;
+    
+    let mut storage = state.storage()?;
     
     if let Some(existing) = storage.accounts.iter_mut().find(|a| a.id == id) {
+        // 激活状态和最近使用时间由切换命令维护，不接受前端写回的值
+        let mut account = Account {
+            is_active: existing.is_active,
+            last_used_at: existing.last_used_at,
+            updated_at: existing.updated_at,
+            ..account
+        };
+        let entry = AuditEntry::for_update(existing, &account);
+        if !entry.changes.is_empty() {
+            account.updated_at = chrono::Utc::now().timestamp_millis();
+        }
         *existing = account.clone();
-        storage.save(&app)?;
+        storage.save_account(&app, &account)?;
+        if !entry.changes.is_empty() {
+            audit::record(&app, &[entry]);
+        }
         Ok(account)
     } else {
-        Err("Account not found".to_string())
+        Err("Account not found".into())
     }
 }
 
@@ -62,34 +187,80 @@ pub fn delete_account(
     app: AppHandle,
     state: State<AppState>,
     id: String,
-) -> Result<(), String> {
-    let mut storage = state.storage.lock().unwrap();
-    storage.accounts.retain(|a| a.id != id);
-    storage.save(&app)?;
+) -> Result<(), AppError> {
+    let mut storage = state.storage()?;
+    let deleted = storage.accounts.iter().find(|a| a.id == id).cloned();
+    if storage.move_to_trash(&id) {
+        storage.remove_account(&app, &id)?;
+    }
+    if let Some(account) = deleted {
+        audit::record(&app, &[AuditEntry::new(AuditAction::Delete, Some(&account), None)]);
+    }
+    storage.auto_purge_trash(&app)?;
     Ok(())
 }
 
+/// 导出账户：可按平台、id、邮箱筛选，并选择完整、打码或仅元数据；提供密码时输出加密导出包
 #[tauri::command]
-pub fn export_accounts(state: State<AppState>) -> Result<String, String> {
-    let storage = state.storage.lock().unwrap();
-    serde_json::to_string_pretty(&storage.accounts)
-        .map_err(|e| format!("Failed to export: {}", e))
+pub fn export_accounts(
+    state: State<AppState>,
+    options: Option<ExportOptions>,
+    passphrase: Option<String>,
+) -> Result<String, AppError> {
+    let storage = state.storage()?;
+    let file = build_export(&storage.accounts, options.unwrap_or_default())?;
+    let json = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to export: {}", e))?;
+    
+    match passphrase.filter(|p| !p.is_empty()) {
+        Some(passphrase) => Ok(seal_bundle(&json, &passphrase)?),
+        None => Ok(json),
+    }
 }
 
+/// 导入账户：按 id 和 (platform, email) 与已有账户合并，dry_run 时只返回报告不保存
+///
+/// 加密导出包需要提供 passphrase，缺少时返回 passphrase_required 错误
 #[tauri::command]
 pub fn import_accounts(
     app: AppHandle,
     state: State<AppState>,
     json: String,
-) -> Result<Vec<Account>, String> {
-    let accounts: Vec<Account> = serde_json::from_str(&json)
-        .map_err(|e| format!("Failed to parse import data: {}", e))?;
+    strategy: Option<MergeStrategy>,
+    dry_run: Option<bool>,
+    passphrase: Option<String>,
+) -> Result<ImportReport, AppError> {
+    let json = open_bundle(&json, passphrase.as_deref())?;
+    let (header, accounts) = parse_import(&json)?;
+    let dry_run = dry_run.unwrap_or(false);
+    
+    let mut storage = state.storage()?;
+    let mut merged = storage.accounts.clone();
+    let mut report = merge_accounts(&mut merged, accounts, strategy.unwrap_or_default());
+    report.dry_run = dry_run;
+    report.source = header;
     
-    let mut storage = state.storage.lock().unwrap();
-    storage.accounts.extend(accounts.clone());
-    storage.save(&app)?;
+    if !dry_run && report.has_changes() {
+        let previous = std::mem::replace(&mut storage.accounts, merged);
+        if let Err(e) = storage.save(&app) {
+            storage.accounts = previous;
+            return Err(e.into());
+        }
+        log_info(format!(
+            "Imported accounts: {} added, {} updated, {} skipped, {} conflicts",
+            report.added.len(), report.updated.len(), report.skipped.len(), report.conflicts.len()
+        ));
+        audit::record(&app, &diff_accounts(AuditAction::Import, &previous, &storage.accounts));
+    }
     
-    Ok(accounts)
+    Ok(report)
+}
+
+/// 修改日志级别：module 为空时修改默认级别（如 `core::storage`、`reqwest`），
+/// level 为空时取消该模块的单独设置。返回修改后的全部设置
+#[tauri::command]
+pub fn set_log_level(module: Option<String>, level: Option<String>) -> Result<std::collections::BTreeMap<String, String>, String> {
+    crate::utils::logger::set_log_level(module.as_deref().unwrap_or_default(), level.as_deref())
 }
 
 /// Get log file path
@@ -101,3 +272,19 @@ pub fn get_log_file_path() -> Result<String, String> {
         .map(|p| p.to_string_lossy().to_string())
         .ok_or_else(|| "Log file not initialized".to_string())
 }
+
+/// 按级别、时间范围、模块和文本查询当前及历史日志
+#[tauri::command]
+#[tracing::instrument(target = "applog", skip_all)]
+pub async fn query_logs(query: Option<LogQuery>) -> Result<Vec<LogEntry>, String> {
+    log_query::query(query.unwrap_or_default()).await
+}
+
+/// 返回最近 lines 条日志（默认 100）；follow 为 true 时开始通过 log-line 事件推送新日志，
+/// 为 false 时停止推送
+#[tauri::command]
+#[tracing::instrument(target = "applog", skip_all)]
+pub async fn tail_logs(app: AppHandle, follow: bool, lines: Option<usize>) -> Result<Vec<LogEntry>, String> {
+    log_query::follow(follow.then_some(app));
+    log_query::query(LogQuery { limit: Some(lines.unwrap_or(100)), ..LogQuery::default() }).await
+}
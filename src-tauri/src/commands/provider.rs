@@ -58,6 +58,7 @@ fn backup_config(path: &PathBuf) -> Result<(), String> {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn get_claude_provider_config(_app: AppHandle) -> Result<ClaudeConfig, String> {
     log_debug("读取 Claude 配置");
     
@@ -81,6 +82,7 @@ pub async fn get_claude_provider_config(_app: AppHandle) -> Result<ClaudeConfig,
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn apply_claude_provider(
     _app: AppHandle,
     config: ClaudeConfig,
@@ -110,6 +112,7 @@ pub async fn apply_claude_provider(
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn get_codex_provider_config(_app: AppHandle) -> Result<CodexConfig, String> {
     log_debug("读取 Codex 配置");
     
@@ -133,6 +136,7 @@ pub async fn get_codex_provider_config(_app: AppHandle) -> Result<CodexConfig, S
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn apply_codex_provider(
     _app: AppHandle,
     config: CodexConfig,
@@ -159,6 +163,7 @@ pub async fn apply_codex_provider(
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn get_gemini_provider_config(_app: AppHandle) -> Result<GeminiConfig, String> {
     log_debug("读取 Gemini 配置");
     
@@ -191,6 +196,7 @@ pub async fn get_gemini_provider_config(_app: AppHandle) -> Result<GeminiConfig,
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn apply_gemini_provider(
     _app: AppHandle,
     config: GeminiConfig,
@@ -220,6 +226,7 @@ pub async fn apply_gemini_provider(
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn get_platform_versions(_app: AppHandle) -> Result<Vec<PlatformVersion>, String> {
     use crate::commands::{claude, codex, gemini, kiro, antigravity};
     
@@ -274,10 +281,7 @@ pub async fn get_platform_versions(_app: AppHandle) -> Result<Vec<PlatformVersio
     });
     
     // Antigravity
-    let antigravity_installed = match crate::utils::config::load_app_config() {
-        Ok(config) => config.antigravity_executable.is_some(),
-        Err(_) => false,
-    };
+    let antigravity_installed = crate::core::settings::load().antigravity_executable.is_some();
     versions.push(PlatformVersion {
         platform: "antigravity".to_string(),
         installed: antigravity_installed,
@@ -0,0 +1,97 @@
+//! 账号切换历史
+//!
+//! 各平台的切换命令成功后由后端记录激活账户和切换历史，
+//! switch_back 据此切换回平台上一个激活的账户。
+
+use crate::commands::{antigravity, claude, codex, gemini, kiro, AppState};
+use crate::core::error::AppError;
+use crate::core::platform::PlatformData;
+use crate::core::storage::SwitchRecord;
+use crate::core::Account;
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+/// 查询切换历史，按时间从新到旧返回
+#[tauri::command]
+pub fn get_switch_history(
+    state: State<AppState>,
+    platform: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<SwitchRecord>, AppError> {
+    let storage = state.storage()?;
+    Ok(storage.switch_history.iter()
+        .rev()
+        .filter(|r| platform.as_ref().is_none_or(|p| &r.platform == p))
+        .take(limit.unwrap_or(usize::MAX))
+        .cloned()
+        .collect())
+}
+
+/// 切换回平台上一个激活的账户，返回切换后的账户
+#[tauri::command]
+#[tracing::instrument(target = "applog", skip_all, fields(platform = %platform))]
+pub async fn switch_back(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    platform: String,
+) -> Result<Account, AppError> {
+    let target = state.storage()?
+        .previous_account(&platform)
+        .cloned()
+        .ok_or_else(|| format!("No previous {} account to switch back to", platform))?;
+    let id = Some(target.id.clone());
+
+    match &target.platform_data {
+        PlatformData::Antigravity(data) => {
+            antigravity::antigravity_switch_account(
+                app.clone(),
+                state.clone(),
+                target.id.clone(),
+                data.token.refresh_token.clone(),
+                target.email.clone(),
+            ).await?;
+        }
+        PlatformData::Kiro(data) => {
+            let credentials = &data.credentials;
+            let auth_method = if data.idp.as_deref() == Some("BuilderId") { "IdC" } else { "social" };
+            kiro::switch_kiro_account(
+                app.clone(),
+                state.clone(),
+                credentials.access_token.clone(),
+                credentials.refresh_token.clone().unwrap_or_default(),
+                credentials.client_id.clone().unwrap_or_default(),
+                credentials.client_secret.clone().unwrap_or_default(),
+                credentials.region.clone(),
+                None,
+                Some(auth_method.to_string()),
+                data.idp.clone(),
+                id,
+            ).await?;
+        }
+        PlatformData::Claude(data) => {
+            let settings = json!(data.config).to_string();
+            claude::switch_claude_account(app.clone(), state.clone(), Some(settings), id).await?;
+        }
+        PlatformData::Codex(data) => {
+            let settings = json!(data.config).to_string();
+            codex::switch_codex_account(app.clone(), state.clone(), Some(settings), id).await?;
+        }
+        PlatformData::Gemini(data) => {
+            let settings = json!({
+                "env": data.config.env.clone().unwrap_or_default(),
+                "config": data.config.config,
+                "settings": data.config.settings,
+            }).to_string();
+            gemini::switch_gemini_account(app.clone(), state.clone(), Some(settings), id).await?;
+        }
+        PlatformData::Unknown(_) => {
+            return Err(format!("Account {} has no usable {} data to switch to", target.id, platform).into());
+        }
+    }
+
+    let storage = state.storage()?;
+    storage.accounts.iter()
+        .find(|a| a.id == target.id)
+        .cloned()
+        .ok_or_else(|| format!("Account {} not found", target.id).into())
+}
@@ -0,0 +1,77 @@
+//! 回收站命令
+//!
+//! 删除的账户先进入回收站，可恢复或彻底清除；超过保留天数后自动清除
+
+use crate::commands::AppState;
+use crate::core::audit::{self, AuditAction, AuditEntry};
+use crate::core::error::AppError;
+use crate::core::settings;
+use crate::core::storage::{get_trash_retention_days, DeletedAccount};
+use crate::core::Account;
+use crate::utils::logger::log_info;
+use tauri::{AppHandle, State};
+
+/// 列出回收站中的账户
+#[tauri::command]
+pub fn list_deleted_accounts(state: State<AppState>) -> Result<Vec<DeletedAccount>, AppError> {
+    let storage = state.storage()?;
+    Ok(storage.trash.clone())
+}
+
+/// 从回收站恢复账户
+#[tauri::command]
+pub fn restore_account(
+    app: AppHandle,
+    state: State<AppState>,
+    id: String,
+) -> Result<Account, AppError> {
+    let mut storage = state.storage()?;
+    let account = storage.restore_from_trash(&id)?;
+    storage.save(&app)?;
+
+    log_info(format!("Restored account {} from trash", id));
+    audit::record(&app, &[AuditEntry::new(AuditAction::Restore, None, Some(&account))]);
+    Ok(account)
+}
+
+/// 彻底清除回收站中的账户，不传 ids 时清空回收站。返回清除数量
+#[tauri::command]
+pub fn purge_trash(
+    app: AppHandle,
+    state: State<AppState>,
+    ids: Option<Vec<String>>,
+) -> Result<usize, AppError> {
+    let mut storage = state.storage()?;
+    let before = storage.trash.clone();
+    let purged = storage.purge_trash(ids.as_deref());
+
+    if purged > 0 {
+        storage.save(&app)?;
+        log_info(format!("Purged {} accounts from trash", purged));
+
+        let entries: Vec<AuditEntry> = before.iter()
+            .filter(|d| !storage.trash.iter().any(|t| t.account.id == d.account.id))
+            .map(|d| AuditEntry::new(AuditAction::Purge, Some(&d.account), None))
+            .collect();
+        audit::record(&app, &entries);
+    }
+    Ok(purged)
+}
+
+/// 获取回收站保留天数
+#[tauri::command]
+pub fn get_trash_retention() -> u64 {
+    get_trash_retention_days()
+}
+
+/// 设置回收站保留天数，0 表示不自动清除
+#[tauri::command]
+pub fn set_trash_retention(
+    app: AppHandle,
+    state: State<AppState>,
+    days: u64,
+) -> Result<(), AppError> {
+    settings::update(|s| s.trash_retention_days = days)?;
+    state.storage()?.auto_purge_trash(&app)?;
+    Ok(())
+}
@@ -0,0 +1,218 @@
+//! Vault 命令
+//!
+//! 启用/关闭账户数据加密、解锁、锁定、修改主密码
+
+use crate::commands::AppState;
+use crate::core::{audit, settings};
+use crate::core::storage::{get_storage_backend, set_vault_status, StorageBackend};
+use crate::core::vault::{VaultKey, VaultState, VaultStatus};
+use crate::core::Storage;
+use crate::utils::logger::log_info;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Vault 状态（返回给前端）
+#[derive(Serialize)]
+pub struct VaultStatusInfo {
+    pub encrypted: bool,
+    pub locked: bool,
+}
+
+/// 获取 Vault 状态，前端启动时据此决定是否显示解锁界面
+#[tauri::command]
+pub fn get_vault_status(vault: State<VaultState>) -> Result<VaultStatusInfo, String> {
+    let status = vault.status.lock().map_err(|e| e.to_string())?;
+    Ok(match &*status {
+        VaultStatus::Plain => VaultStatusInfo { encrypted: false, locked: false },
+        VaultStatus::Locked => VaultStatusInfo { encrypted: true, locked: true },
+        VaultStatus::Unlocked(_) => VaultStatusInfo { encrypted: true, locked: false },
+    })
+}
+
+/// 使用主密码解锁存储
+#[tauri::command]
+pub fn unlock_vault(
+    app: AppHandle,
+    state: State<AppState>,
+    passphrase: String,
+) -> Result<(), String> {
+    let mut storage = Storage::unlock(&app, &passphrase)?;
+    storage.auto_purge_trash(&app)?;
+    *state.storage.lock().unwrap() = storage;
+    state.touch();
+    Ok(())
+}
+
+/// 立即锁定 Vault
+#[tauri::command]
+pub fn lock_vault(app: AppHandle) -> Result<(), String> {
+    lock_now(&app)
+}
+
+/// 获取空闲自动锁定时间（分钟）
+#[tauri::command]
+pub fn get_auto_lock_minutes(vault: State<VaultState>) -> Result<u64, String> {
+    Ok(*vault.auto_lock_minutes.lock().map_err(|e| e.to_string())?)
+}
+
+/// 设置空闲自动锁定时间（分钟），0 表示关闭自动锁定
+#[tauri::command]
+pub fn set_auto_lock_minutes(vault: State<VaultState>, minutes: u64) -> Result<(), String> {
+    settings::update(|s| s.auto_lock_minutes = minutes)?;
+    *vault.auto_lock_minutes.lock().map_err(|e| e.to_string())? = minutes;
+    Ok(())
+}
+
+/// 锁定 Vault：从内存中丢弃所有密钥数据，只保留账户元数据
+///
+/// 供 lock_vault 命令、托盘菜单和空闲计时器共用
+pub fn lock_now(app: &AppHandle) -> Result<(), String> {
+    {
+        // 先检查再写入：明文存储不能有任何时刻处于 Locked，否则并发的读写会失败
+        let vault = app.try_state::<VaultState>().ok_or("Vault state not initialized")?;
+        let mut status = vault.status.lock().map_err(|e| e.to_string())?;
+        match &*status {
+            VaultStatus::Unlocked(_) => *status = VaultStatus::Locked,
+            VaultStatus::Locked => return Ok(()),
+            VaultStatus::Plain => return Err("Vault encryption is not enabled".to_string()),
+        }
+    }
+
+    if let Some(state) = app.try_state::<AppState>() {
+        let mut storage = state.storage.lock().unwrap();
+        *storage = storage.locked_view();
+    }
+
+    let _ = app.emit("vault-locked", ());
+    log_info("Vault locked");
+    Ok(())
+}
+
+/// 后台空闲检测：超过设定时间没有访问账户数据时自动锁定
+pub async fn run_idle_lock_timer(app: AppHandle) {
+    const CHECK_INTERVAL_SECS: u64 = 30;
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+
+        let minutes = match app.try_state::<VaultState>() {
+            Some(vault) => match vault.auto_lock_minutes.lock() {
+                Ok(minutes) => *minutes,
+                Err(_) => continue,
+            },
+            None => continue,
+        };
+        if minutes == 0 {
+            continue;
+        }
+
+        let idle = match app.try_state::<AppState>() {
+            Some(state) => match state.last_activity.lock() {
+                Ok(last) => last.elapsed(),
+                Err(_) => continue,
+            },
+            None => continue,
+        };
+
+        if idle >= Duration::from_secs(minutes * 60) {
+            // 明文存储或已锁定时 lock_now 会直接返回，无需处理
+            if lock_now(&app).is_ok() {
+                log_info(format!("Vault auto-locked after {} minutes idle", minutes));
+            }
+        }
+    }
+}
+
+/// 启用加密：将当前明文存储原地加密
+#[tauri::command]
+pub fn enable_vault_encryption(
+    app: AppHandle,
+    state: State<AppState>,
+    vault: State<VaultState>,
+    passphrase: String,
+) -> Result<(), String> {
+    if get_storage_backend() != StorageBackend::Json {
+        return Err("Vault encryption is only supported by the JSON storage backend".to_string());
+    }
+
+    let key = VaultKey::generate(&passphrase)?;
+
+    // 先持有存储锁，切换状态到写入完成之间不会有其他保存
+    let mut storage = state.storage.lock().unwrap();
+    {
+        let mut status = vault.status.lock().map_err(|e| e.to_string())?;
+        if !matches!(*status, VaultStatus::Plain) {
+            return Err("Vault encryption is already enabled".to_string());
+        }
+        *status = VaultStatus::Unlocked(key);
+    }
+
+    if let Err(e) = storage.save(&app) {
+        set_vault_status(&app, VaultStatus::Plain)?;
+        return Err(e);
+    }
+
+    audit::strip_values(&app);
+    log_info("Vault encryption enabled");
+    Ok(())
+}
+
+/// 关闭加密：验证主密码后以明文重新写入
+#[tauri::command]
+pub fn disable_vault_encryption(
+    app: AppHandle,
+    state: State<AppState>,
+    passphrase: String,
+) -> Result<(), String> {
+    let mut storage = state.storage.lock().unwrap();
+    let previous = verify_passphrase(&app, &passphrase, VaultStatus::Plain)?;
+
+    if let Err(e) = storage.save(&app) {
+        set_vault_status(&app, previous)?;
+        return Err(e);
+    }
+
+    log_info("Vault encryption disabled");
+    Ok(())
+}
+
+/// 修改主密码（同时更换盐值）
+#[tauri::command]
+pub fn change_vault_passphrase(
+    app: AppHandle,
+    state: State<AppState>,
+    current_passphrase: String,
+    new_passphrase: String,
+) -> Result<(), String> {
+    let new_key = VaultKey::generate(&new_passphrase)?;
+    let mut storage = state.storage.lock().unwrap();
+    let previous = verify_passphrase(&app, &current_passphrase, VaultStatus::Unlocked(new_key))?;
+
+    if let Err(e) = storage.save(&app) {
+        set_vault_status(&app, previous)?;
+        return Err(e);
+    }
+
+    log_info("Vault passphrase changed");
+    Ok(())
+}
+
+/// 验证当前主密码，通过后切换到新状态并返回旧状态（用于失败回滚）
+///
+/// 验证和切换在同一次状态锁内完成，验证期间状态保持不变。
+/// 调用方需先持有存储锁，保证切换后到自己保存前没有其他写入
+fn verify_passphrase(app: &AppHandle, passphrase: &str, next: VaultStatus) -> Result<VaultStatus, String> {
+    let vault = app.try_state::<VaultState>().ok_or("Vault state not initialized")?;
+    let mut status = vault.status.lock().map_err(|e| e.to_string())?;
+
+    match &*status {
+        VaultStatus::Plain => return Err("Vault encryption is not enabled".to_string()),
+        VaultStatus::Locked => return Err(crate::core::vault::VAULT_LOCKED.to_string()),
+        VaultStatus::Unlocked(key) if !key.matches(passphrase) => {
+            return Err("Invalid passphrase".to_string());
+        }
+        VaultStatus::Unlocked(_) => {}
+    }
+    Ok(std::mem::replace(&mut *status, next))
+}
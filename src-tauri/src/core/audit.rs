@@ -0,0 +1,365 @@
+//! 审计日志
+//!
+//! 每次账户变更（添加、修改、删除、切换、导入、Token 刷新等）都在存储文件旁的
+//! `audit.jsonl` 中追加一行记录，包含时间、操作、账户 id、平台和字段级差异。
+//! 差异中的 Token、密钥等敏感字段会被打码，日志本身不含可用凭据。
+//!
+//! 审计日志是明文：启用加密后只记录账户 id 和字段名，不记录字段的新旧值
+//! （启用时已有的记录也会去掉值）。文件超过 MAX_AUDIT_BYTES 时轮转为 `audit.jsonl.1`，只保留一份历史。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::storage::{get_storage_path, is_vault_unlocked, Account};
+use crate::utils::atomic_file::write_atomic;
+use crate::utils::logger::log_warn;
+use crate::utils::redact::{is_secret_key, mask_value};
+
+/// 审计日志文件名（与存储文件同目录）
+const AUDIT_FILE: &str = "audit.jsonl";
+
+/// 审计日志超过该大小时轮转
+const MAX_AUDIT_BYTES: u64 = 5 * 1024 * 1024;
+
+/// 账户操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Add,
+    Update,
+    /// 移入回收站
+    Delete,
+    /// 从回收站或快照恢复
+    Restore,
+    /// 从回收站彻底清除
+    Purge,
+    Switch,
+    TokenRefresh,
+    Import,
+}
+
+/// 单个字段的变化，field 为点分路径，如 `platform_data.token.access_token`。
+/// 启用加密后不记录新旧值，两者均为 null
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub old: Value,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub new: Value,
+}
+
+/// 审计记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: i64,
+    pub action: AuditAction,
+    pub account_id: String,
+    pub platform: String,
+    #[serde(default)]
+    pub changes: Vec<FieldChange>,
+}
+
+impl AuditEntry {
+    /// 根据变更前后的账户生成记录；before 为 None 表示新增，after 为 None 表示移除
+    pub fn new(action: AuditAction, before: Option<&Account>, after: Option<&Account>) -> Self {
+        let account = after.or(before).expect("audit entry requires an account");
+        Self {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            action,
+            account_id: account.id.clone(),
+            platform: account.platform.clone(),
+            changes: diff(before, after),
+        }
+    }
+
+    /// 修改账户时按差异推断操作类型：仅凭据变化视为 Token 刷新
+    pub fn for_update(before: &Account, after: &Account) -> Self {
+        let mut entry = Self::new(AuditAction::Update, Some(before), Some(after));
+        if !entry.changes.is_empty() && entry.changes.iter().all(|c| is_credential_field(&c.field)) {
+            entry.action = AuditAction::TokenRefresh;
+        }
+        entry
+    }
+
+    /// 去掉字段的新旧值，只保留字段名
+    fn without_values(mut self) -> Self {
+        for change in &mut self.changes {
+            change.old = Value::Null;
+            change.new = Value::Null;
+        }
+        self
+    }
+}
+
+/// Token 刷新会改变的字段：凭据本身及其过期时间
+fn is_credential_field(field: &str) -> bool {
+    field.starts_with("platform_data.")
+        && field.split('.').any(|part| is_secret_key(part) || part.to_lowercase().contains("expir"))
+}
+
+/// 计算字段级差异，平台数据展开到叶子字段，敏感字段打码
+pub fn diff(before: Option<&Account>, after: Option<&Account>) -> Vec<FieldChange> {
+    let to_value = |account: Option<&Account>| {
+        account.and_then(|a| serde_json::to_value(a).ok()).unwrap_or(Value::Null)
+    };
+
+    let mut changes = Vec::new();
+    diff_values("", &to_value(before), &to_value(after), &mut changes);
+    changes
+}
+
+fn diff_values(path: &str, old: &Value, new: &Value, changes: &mut Vec<FieldChange>) {
+    if old == new {
+        return;
+    }
+
+    if let (Value::Object(_) | Value::Null, Value::Object(_) | Value::Null) = (old, new) {
+        let empty = serde_json::Map::new();
+        let old_map = old.as_object().unwrap_or(&empty);
+        let new_map = new.as_object().unwrap_or(&empty);
+
+        let mut keys: Vec<&String> = old_map.keys().chain(new_map.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let child = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+            diff_values(
+                &child,
+                old_map.get(key).unwrap_or(&Value::Null),
+                new_map.get(key).unwrap_or(&Value::Null),
+                changes,
+            );
+        }
+        return;
+    }
+
+    let (mut old, mut new) = (old.clone(), new.clone());
+    if path.split('.').any(is_secret_key) {
+        mask_value(&mut old);
+        mask_value(&mut new);
+    }
+    changes.push(FieldChange { field: path.to_string(), old, new });
+}
+
+pub(crate) fn audit_path(storage_path: &Path) -> PathBuf {
+    storage_path
+        .parent()
+        .map(|dir| dir.join(AUDIT_FILE))
+        .unwrap_or_else(|| PathBuf::from(AUDIT_FILE))
+}
+
+/// 轮转后的上一份审计日志
+pub(crate) fn rotated_path(audit_path: &Path) -> PathBuf {
+    audit_path.with_extension("jsonl.1")
+}
+
+/// 追加审计记录。写入失败只记录警告，不影响账户操作本身
+pub fn record(app: &AppHandle, entries: &[AuditEntry]) {
+    if entries.is_empty() {
+        return;
+    }
+
+    // 只有解锁后才能修改账户，此时已启用加密
+    let entries: Vec<AuditEntry> = if is_vault_unlocked(app) {
+        entries.iter().cloned().map(AuditEntry::without_values).collect()
+    } else {
+        entries.to_vec()
+    };
+    let result = get_storage_path(app).and_then(|path| append(&audit_path(&path), &entries));
+    if let Err(e) = result {
+        log_warn(format!("Failed to write audit log: {}", e));
+    }
+}
+
+/// 启用加密时去掉已有记录中的新旧值
+pub fn strip_values(app: &AppHandle) {
+    let Ok(storage_path) = get_storage_path(app) else {
+        return;
+    };
+    let path = audit_path(&storage_path);
+    for file in [rotated_path(&path), path] {
+        if let Err(e) = rewrite_without_values(&file) {
+            log_warn(format!("Failed to strip values from {}: {}", file.display(), e));
+        }
+    }
+}
+
+fn rewrite_without_values(path: &Path) -> Result<(), String> {
+    let entries = match read_file(path) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let entries: Vec<AuditEntry> = entries.into_iter().map(AuditEntry::without_values).collect();
+    write_atomic(path, to_lines(&entries)?.as_bytes())
+}
+
+fn to_lines(entries: &[AuditEntry]) -> Result<String, String> {
+    let mut lines = String::new();
+    for entry in entries {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+        lines.push_str(&line);
+        lines.push('\n');
+    }
+    Ok(lines)
+}
+
+fn append(path: &Path, entries: &[AuditEntry]) -> Result<(), String> {
+    let lines = to_lines(entries)?;
+
+    // 超过上限时轮转，旧的历史被覆盖
+    let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    if size > 0 && size + lines.len() as u64 > MAX_AUDIT_BYTES {
+        fs::rename(path, rotated_path(path))
+            .map_err(|e| format!("Failed to rotate {}: {}", path.display(), e))?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    file.write_all(lines.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// 比较两组账户，为新增、变化和移除的账户生成记录（用于导入、快照恢复等批量操作）
+pub fn diff_accounts(action: AuditAction, before: &[Account], after: &[Account]) -> Vec<AuditEntry> {
+    let mut entries = Vec::new();
+    for account in after {
+        match before.iter().find(|a| a.id == account.id) {
+            None => entries.push(AuditEntry::new(action, None, Some(account))),
+            Some(old) => {
+                let entry = AuditEntry::new(action, Some(old), Some(account));
+                if !entry.changes.is_empty() {
+                    entries.push(entry);
+                }
+            }
+        }
+    }
+    for account in before.iter().filter(|a| !after.iter().any(|b| b.id == a.id)) {
+        entries.push(AuditEntry::new(action, Some(account), None));
+    }
+    entries
+}
+
+/// 审计日志查询条件，时间为毫秒时间戳
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AuditFilter {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub account_id: Option<String>,
+    pub action: Option<AuditAction>,
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp <= until)
+            && self.account_id.as_ref().is_none_or(|id| &entry.account_id == id)
+            && self.action.is_none_or(|action| entry.action == action)
+    }
+}
+
+/// 读取一个审计日志文件，无法解析的行会被跳过
+fn read_file(path: &Path) -> std::io::Result<Vec<AuditEntry>> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// 读取审计日志（包括轮转后的上一份），按时间从新到旧返回
+pub fn read(path: &Path, filter: &AuditFilter) -> Result<Vec<AuditEntry>, String> {
+    let mut entries = Vec::new();
+    for file in [rotated_path(path), path.to_path_buf()] {
+        match read_file(&file) {
+            Ok(found) => entries.extend(found.into_iter().filter(|entry| filter.matches(entry))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to read audit log: {}", e)),
+        }
+    }
+    entries.reverse();
+    entries.truncate(filter.limit.unwrap_or(usize::MAX));
+    Ok(entries)
+}
+
+/// 查询审计日志
+#[tauri::command]
+pub fn get_audit_log(app: AppHandle, filter: Option<AuditFilter>) -> Result<Vec<AuditEntry>, String> {
+    let path = audit_path(&get_storage_path(&app)?);
+    read(&path, &filter.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{account_with_data, temp_dir};
+    use serde_json::json;
+
+    fn account(access_token: &str) -> Account {
+        account_with_data("a", "antigravity", "a@example.com", json!({
+            "token": { "access_token": access_token, "refresh_token": "1//0abcdefghijklmnopqrstuvwxyz", "expires_in": 3600 }
+        }))
+    }
+
+    #[test]
+    fn test_update_classification_and_redaction() {
+        let before = account("ya29.old-access-token-value");
+        let mut after = account("ya29.new-access-token-value");
+
+        let entry = AuditEntry::for_update(&before, &after);
+        assert_eq!(entry.action, AuditAction::TokenRefresh);
+        assert_eq!(entry.changes, vec![FieldChange {
+            field: "platform_data.token.access_token".to_string(),
+            old: json!("ya29****alue"),
+            new: json!("ya29****alue"),
+        }]);
+
+        after.notes = Some("shared".to_string());
+        assert_eq!(AuditEntry::for_update(&before, &after).action, AuditAction::Update);
+    }
+
+    #[test]
+    fn test_append_and_filter() {
+        let dir = temp_dir("audit");
+        let path = dir.join(AUDIT_FILE);
+
+        let mut other = account("x");
+        other.id = "b".to_string();
+        let entries = diff_accounts(AuditAction::Import, &[], &[account("x"), other]);
+        append(&path, &entries).unwrap();
+        append(&path, &[AuditEntry::new(AuditAction::Delete, Some(&account("x")), None)]).unwrap();
+
+        let all = read(&path, &AuditFilter::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].action, AuditAction::Delete);
+
+        let filter = AuditFilter { account_id: Some("a".to_string()), action: Some(AuditAction::Import), ..Default::default() };
+        assert_eq!(read(&path, &filter).unwrap().len(), 1);
+
+        // 去掉新旧值后只剩字段名
+        rewrite_without_values(&path).unwrap();
+        let stripped = read(&path, &AuditFilter::default()).unwrap();
+        assert_eq!(stripped.len(), 3);
+        assert!(stripped.iter().flat_map(|e| &e.changes).all(|c| c.old.is_null() && c.new.is_null()));
+        assert!(!fs::read_to_string(&path).unwrap().contains("a@example.com"));
+
+        // 超过上限时轮转，读取时包括上一份
+        fs::write(&path, vec![b'\n'; MAX_AUDIT_BYTES as usize]).unwrap();
+        append(&path, &[AuditEntry::new(AuditAction::Add, None, Some(&account("x")))]).unwrap();
+        assert!(rotated_path(&path).exists());
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+        assert_eq!(read(&path, &AuditFilter::default()).unwrap().len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,375 @@
+//! 存储一致性检查与修复
+//!
+//! 检查 Storage 中应当成立的约束：账户 id 唯一、每个平台最多一个激活账户、
+//! 机器码绑定指向存在的账户等。修复按指定策略进行，无法自动修复的问题原样报告。
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, State};
+
+use super::platform::PlatformData;
+use super::storage::{Account, Storage};
+use crate::commands::AppState;
+use crate::core::error::AppError;
+use crate::utils::logger::log_info;
+
+/// 一致性问题
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StorageIssue {
+    /// 多个账户使用同一个 id
+    DuplicateId { id: String, count: usize },
+    /// 同一平台有多个激活账户
+    MultipleActive { platform: String, ids: Vec<String> },
+    /// 机器码绑定指向不存在的账户（回收站中的账户仍视为存在）
+    OrphanBinding { account_id: String, machine_id: String },
+    /// 回收站中的账户与现有账户 id 相同，恢复时会产生重复。
+    /// updated_at 为现有账户的修改时间，deleted_at 为回收站中账户的删除时间
+    TrashConflict {
+        id: String,
+        active_email: String,
+        trashed_email: String,
+        updated_at: i64,
+        deleted_at: i64,
+    },
+    /// 已知平台的账户数据无法解析，需要手动修改（不会自动修复）
+    InvalidPlatformData { id: String, platform: String, error: String },
+}
+
+/// 重复 id 的处理方式，也用于现有账户与回收站中的账户 id 相同的情况
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    /// 保留最近使用的一个，删除其余
+    #[default]
+    KeepMostRecent,
+    /// 保留列表中的第一个，删除其余
+    KeepFirst,
+    /// 全部保留，为重复的账户分配新 id
+    Reassign,
+}
+
+/// 多个激活账户的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivePolicy {
+    /// 保留最近使用的账户为激活状态
+    #[default]
+    KeepMostRecent,
+    /// 全部取消激活
+    DeactivateAll,
+}
+
+/// 修复策略
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct RepairPolicy {
+    pub duplicates: DuplicatePolicy,
+    pub active: ActivePolicy,
+}
+
+/// 修复结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RepairReport {
+    pub fixed: Vec<StorageIssue>,
+    /// 修复后仍存在的问题
+    pub remaining: Vec<StorageIssue>,
+    pub dry_run: bool,
+}
+
+/// 检查存储，返回所有违反约束的问题
+pub fn check(storage: &Storage) -> Vec<StorageIssue> {
+    let mut issues = Vec::new();
+
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for account in &storage.accounts {
+        match counts.iter_mut().find(|(id, _)| *id == account.id) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((&account.id, 1)),
+        }
+    }
+    issues.extend(counts.into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|(id, count)| StorageIssue::DuplicateId { id: id.to_string(), count }));
+
+    let mut active: Vec<(&str, Vec<String>)> = Vec::new();
+    for account in storage.accounts.iter().filter(|a| a.is_active) {
+        match active.iter_mut().find(|(platform, _)| *platform == account.platform) {
+            Some((_, ids)) => ids.push(account.id.clone()),
+            None => active.push((&account.platform, vec![account.id.clone()])),
+        }
+    }
+    issues.extend(active.into_iter()
+        .filter(|(_, ids)| ids.len() > 1)
+        .map(|(platform, ids)| StorageIssue::MultipleActive { platform: platform.to_string(), ids }));
+
+    let known: HashSet<&str> = storage.accounts.iter()
+        .chain(storage.trash.iter().map(|d| &d.account))
+        .map(|a| a.id.as_str())
+        .collect();
+    let mut orphans: Vec<(&String, &String)> = storage.account_machine_bindings.iter()
+        .filter(|(id, _)| !known.contains(id.as_str()))
+        .collect();
+    orphans.sort();
+    issues.extend(orphans.into_iter()
+        .map(|(id, machine_id)| StorageIssue::OrphanBinding { account_id: id.clone(), machine_id: machine_id.clone() }));
+
+    for deleted in &storage.trash {
+        if let Some(active) = storage.accounts.iter().find(|a| a.id == deleted.account.id) {
+            issues.push(StorageIssue::TrashConflict {
+                id: active.id.clone(),
+                active_email: active.email.clone(),
+                trashed_email: deleted.account.email.clone(),
+                updated_at: active.updated_at,
+                deleted_at: deleted.deleted_at,
+            });
+        }
+    }
+
+    for account in &storage.accounts {
+        if let PlatformData::Unknown(value) = &account.platform_data {
+            // 锁定或导出时去掉的数据为 Null，不属于损坏
+            if value.is_null() {
+                continue;
+            }
+            if let Err(error) = PlatformData::parse(&account.platform, value.clone()) {
+                issues.push(StorageIssue::InvalidPlatformData {
+                    id: account.id.clone(),
+                    platform: account.platform.clone(),
+                    error,
+                });
+            }
+        }
+    }
+
+    issues
+}
+
+/// 按策略修复存储，返回修复报告
+pub fn repair(storage: &mut Storage, policy: &RepairPolicy) -> RepairReport {
+    let before = check(storage);
+
+    dedupe_ids(&mut storage.accounts, policy.duplicates);
+
+    let mut by_platform: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, account) in storage.accounts.iter().enumerate().filter(|(_, a)| a.is_active) {
+        by_platform.entry(account.platform.clone()).or_default().push(index);
+    }
+    for indexes in by_platform.into_values().filter(|i| i.len() > 1) {
+        let keep = match policy.active {
+            ActivePolicy::KeepMostRecent => most_recent(&storage.accounts, &indexes),
+            ActivePolicy::DeactivateAll => None,
+        };
+        for index in indexes {
+            storage.accounts[index].is_active = Some(index) == keep;
+        }
+    }
+
+    let known: HashSet<String> = storage.accounts.iter()
+        .chain(storage.trash.iter().map(|d| &d.account))
+        .map(|a| a.id.clone())
+        .collect();
+    storage.account_machine_bindings.retain(|id, _| known.contains(id));
+
+    resolve_trash_conflicts(storage, policy.duplicates);
+
+    let remaining = check(storage);
+    RepairReport {
+        fixed: before.into_iter().filter(|issue| !remaining.contains(issue)).collect(),
+        remaining,
+        dry_run: false,
+    }
+}
+
+/// 为与前面账户 id 重复的账户分配新 id，返回重新分配的数量。
+/// 用于导入等不能丢弃账户的场合（如 SQLite 以 id 为主键，重复 id 会相互覆盖）
+pub(crate) fn reassign_duplicate_ids(accounts: &mut Vec<Account>) -> usize {
+    dedupe_ids(accounts, DuplicatePolicy::Reassign)
+}
+
+/// 按策略处理重复 id，返回重新分配或移除的账户数
+fn dedupe_ids(accounts: &mut Vec<Account>, policy: DuplicatePolicy) -> usize {
+    let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, account) in accounts.iter().enumerate() {
+        groups.entry(account.id.clone()).or_default().push(index);
+    }
+
+    let mut removed = HashSet::new();
+    let mut handled = 0;
+    for indexes in groups.into_values().filter(|i| i.len() > 1) {
+        let keep = match policy {
+            DuplicatePolicy::KeepMostRecent => most_recent(accounts, &indexes),
+            DuplicatePolicy::KeepFirst | DuplicatePolicy::Reassign => indexes.first().copied(),
+        };
+        for index in indexes.into_iter().filter(|i| Some(*i) != keep) {
+            handled += 1;
+            if policy == DuplicatePolicy::Reassign {
+                accounts[index].id = uuid::Uuid::new_v4().to_string();
+            } else {
+                removed.insert(index);
+            }
+        }
+    }
+
+    let mut index = 0;
+    accounts.retain(|_| {
+        index += 1;
+        !removed.contains(&(index - 1))
+    });
+    handled
+}
+
+/// 处理与现有账户 id 相同的回收站账户：Reassign 时为回收站中的账户分配新 id；
+/// KeepMostRecent 时保留较新的一方（删除晚于现有账户的最后修改时保留删除，否则保留现有账户）；
+/// KeepFirst 时保留现有账户
+fn resolve_trash_conflicts(storage: &mut Storage, policy: DuplicatePolicy) {
+    let mut removed_accounts = HashSet::new();
+    let mut removed_trash = HashSet::new();
+    for (trash_index, deleted) in storage.trash.iter_mut().enumerate() {
+        let Some(index) = storage.accounts.iter().position(|a| a.id == deleted.account.id) else {
+            continue;
+        };
+        match policy {
+            DuplicatePolicy::Reassign => deleted.account.id = uuid::Uuid::new_v4().to_string(),
+            DuplicatePolicy::KeepMostRecent if deleted.deleted_at > storage.accounts[index].updated_at => {
+                removed_accounts.insert(index);
+            }
+            _ => {
+                removed_trash.insert(trash_index);
+            }
+        }
+    }
+
+    let mut index = 0;
+    storage.accounts.retain(|_| {
+        index += 1;
+        !removed_accounts.contains(&(index - 1))
+    });
+    let mut index = 0;
+    storage.trash.retain(|_| {
+        index += 1;
+        !removed_trash.contains(&(index - 1))
+    });
+}
+
+/// 最近使用的账户；时间相同时取靠前的
+fn most_recent(accounts: &[Account], indexes: &[usize]) -> Option<usize> {
+    indexes.iter().copied().reduce(|best, i| {
+        if accounts[i].last_used_at > accounts[best].last_used_at { i } else { best }
+    })
+}
+
+/// 检查存储一致性
+#[tauri::command]
+pub fn check_storage(state: State<AppState>) -> Result<Vec<StorageIssue>, AppError> {
+    let storage = state.storage()?;
+    Ok(check(&storage))
+}
+
+/// 按策略修复存储；dry_run 时只返回将要修复的问题
+#[tauri::command]
+pub fn repair_storage(
+    app: AppHandle,
+    state: State<AppState>,
+    policy: Option<RepairPolicy>,
+    dry_run: Option<bool>,
+) -> Result<RepairReport, AppError> {
+    let mut storage = state.storage()?;
+    let mut repaired = storage.clone();
+    let mut report = repair(&mut repaired, &policy.unwrap_or_default());
+    report.dry_run = dry_run.unwrap_or(false);
+
+    if !report.dry_run && !report.fixed.is_empty() {
+        repaired.save(&app)?;
+        *storage = repaired;
+        log_info(format!("Repaired storage: {} issues fixed, {} remaining", report.fixed.len(), report.remaining.len()));
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::storage::DeletedAccount;
+    use crate::core::test_support;
+
+    fn account(id: &str, platform: &str, active: bool, last_used_at: i64) -> Account {
+        Account {
+            is_active: active,
+            last_used_at,
+            ..test_support::account(id, platform, &format!("{}@example.com", id))
+        }
+    }
+
+    fn broken_storage() -> Storage {
+        let mut storage = Storage::new();
+        storage.accounts = vec![
+            account("a", "claude", true, 10),
+            account("a", "claude", false, 20),
+            account("b", "claude", true, 30),
+            account("c", "kiro", false, 0),
+        ];
+        storage.trash.push(DeletedAccount { account: account("c", "kiro", false, 0), deleted_at: 0 });
+        storage.account_machine_bindings.insert("gone".to_string(), "m1".to_string());
+        storage.account_machine_bindings.insert("c".to_string(), "m2".to_string());
+        storage
+    }
+
+    #[test]
+    fn test_check() {
+        let issues = check(&broken_storage());
+        assert_eq!(issues, vec![
+            StorageIssue::DuplicateId { id: "a".to_string(), count: 2 },
+            StorageIssue::MultipleActive { platform: "claude".to_string(), ids: vec!["a".to_string(), "b".to_string()] },
+            StorageIssue::OrphanBinding { account_id: "gone".to_string(), machine_id: "m1".to_string() },
+            StorageIssue::TrashConflict {
+                id: "c".to_string(),
+                active_email: "c@example.com".to_string(),
+                trashed_email: "c@example.com".to_string(),
+                updated_at: 0,
+                deleted_at: 0,
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_repair_policies() {
+        let mut storage = broken_storage();
+        let report = repair(&mut storage, &RepairPolicy::default());
+        assert_eq!(report.fixed.len(), 4);
+        assert!(report.remaining.is_empty());
+
+        // 重复的 a 保留最近使用的一个（未激活），b 最近使用，保持激活
+        let ids: Vec<_> = storage.accounts.iter().map(|a| (a.id.as_str(), a.is_active)).collect();
+        assert_eq!(ids, vec![("a", false), ("b", true), ("c", false)]);
+        assert!(storage.trash.is_empty());
+        assert_eq!(storage.account_machine_bindings.len(), 1);
+
+        let mut storage = broken_storage();
+        let policy = RepairPolicy { duplicates: DuplicatePolicy::Reassign, active: ActivePolicy::DeactivateAll };
+        repair(&mut storage, &policy);
+        assert_eq!(storage.accounts.len(), 4);
+        assert!(storage.accounts.iter().all(|a| !a.is_active));
+        // 回收站中的 c 分配了新 id，两份都保留
+        assert_eq!(storage.trash.len(), 1);
+        assert_ne!(storage.trash[0].account.id, "c");
+        assert!(check(&storage).is_empty());
+    }
+
+    #[test]
+    fn test_trash_conflict_keeps_newer() {
+        // 现有账户在删除之后没有修改过：保留删除
+        let mut storage = Storage::new();
+        storage.accounts = vec![Account { updated_at: 10, ..account("c", "kiro", false, 0) }];
+        storage.trash.push(DeletedAccount { account: account("c", "kiro", false, 0), deleted_at: 20 });
+        repair(&mut storage, &RepairPolicy::default());
+        assert!(storage.accounts.is_empty());
+        assert_eq!(storage.trash.len(), 1);
+
+        // 删除之后又修改过：保留现有账户
+        storage.accounts = vec![Account { updated_at: 30, ..account("c", "kiro", false, 0) }];
+        repair(&mut storage, &RepairPolicy::default());
+        assert_eq!(storage.accounts.len(), 1);
+        assert!(storage.trash.is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+//! 命令错误类型
+//!
+//! 序列化为 `{ kind, message }`，前端可按 kind 区分错误，同时仍可直接读取 message
+
+use serde::Serialize;
+use std::fmt;
+
+use super::storage::STORAGE_CONFLICT;
+use super::vault::VAULT_LOCKED;
+
+/// 错误类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Vault 已锁定，需要先解锁
+    Locked,
+    /// 数据已加密，需要提供密码
+    PassphraseRequired,
+    /// 密码错误或数据已损坏
+    InvalidPassphrase,
+    /// 存储文件已被其他进程修改，重新读取合并后可重试
+    Conflict,
+    /// 目标位置已有存储，需要选择处理方式
+    StorageExists,
+    /// 其他错误
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppError {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl AppError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn locked() -> Self {
+        Self::new(ErrorKind::Locked, VAULT_LOCKED)
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        // Storage::save 在锁定时返回字符串错误，这里统一转换为 Locked
+        if message == VAULT_LOCKED {
+            return Self::locked();
+        }
+        if message.starts_with(STORAGE_CONFLICT) {
+            return Self::new(ErrorKind::Conflict, message);
+        }
+        Self {
+            kind: ErrorKind::Other,
+            message,
+        }
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        Self::from(message.to_string())
+    }
+}
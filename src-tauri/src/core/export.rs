@@ -0,0 +1,226 @@
+//! 账户导出
+//!
+//! 导出文件带有 header，记录导出模式和筛选条件；导入时据此判断收到的是什么。
+//! 旧版本导出的纯账户数组仍可导入。
+//! 设置密码时，整个导出文件用 Vault 相同的算法（Argon2id + ChaCha20-Poly1305）加密为导出包。
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::error::{AppError, ErrorKind};
+use super::platform::PlatformData;
+use super::storage::Account;
+use super::vault::{self, VaultKey};
+use crate::utils::redact::redact_value;
+
+/// 导出文件格式标识
+pub const EXPORT_FORMAT: &str = "nexus-export";
+/// 导出文件格式版本
+pub const EXPORT_VERSION: u32 = 1;
+
+/// 加密导出包格式标识
+pub const BUNDLE_FORMAT: &str = "nexus-export-bundle";
+
+/// 导出模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportMode {
+    /// 包含完整的 Token 和密钥
+    #[default]
+    Full,
+    /// 密钥打码，邮箱等元数据保留
+    Redacted,
+    /// 仅元数据，不含 platform_data
+    Metadata,
+}
+
+/// 导出选项，筛选条件之间为“且”的关系
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportOptions {
+    pub mode: ExportMode,
+    pub platforms: Option<Vec<String>>,
+    pub ids: Option<Vec<String>>,
+    /// 邮箱通配符，支持 `*` 和 `?`，忽略大小写，如 `*@example.com`
+    pub email_pattern: Option<String>,
+}
+
+/// 导出文件头
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportHeader {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub exported_at: i64,
+    pub account_count: usize,
+    #[serde(flatten)]
+    pub options: ExportOptions,
+}
+
+/// 导出文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportFile {
+    pub header: ExportHeader,
+    pub accounts: Vec<Account>,
+}
+
+/// 将邮箱通配符转换为正则
+fn email_matcher(pattern: &str) -> Result<Regex, String> {
+    let escaped = regex::escape(pattern).replace(r"\*", ".*").replace(r"\?", ".");
+    Regex::new(&format!("(?i)^{}$", escaped))
+        .map_err(|e| format!("Invalid email pattern: {}", e))
+}
+
+/// 按选项筛选并处理账户，生成导出文件
+pub fn build_export(accounts: &[Account], options: ExportOptions) -> Result<ExportFile, String> {
+    let email_matcher = options.email_pattern.as_deref().map(email_matcher).transpose()?;
+
+    let accounts: Vec<Account> = accounts
+        .iter()
+        .filter(|a| options.platforms.as_ref().is_none_or(|p| p.contains(&a.platform)))
+        .filter(|a| options.ids.as_ref().is_none_or(|ids| ids.contains(&a.id)))
+        .filter(|a| email_matcher.as_ref().is_none_or(|re| re.is_match(&a.email)))
+        .cloned()
+        .map(|mut account| {
+            match options.mode {
+                ExportMode::Full => {}
+                ExportMode::Redacted => {
+                    let mut data = account.platform_data.to_value();
+                    redact_value(&mut data);
+                    account.platform_data = PlatformData::Unknown(data);
+                }
+                ExportMode::Metadata => account.platform_data = PlatformData::Unknown(serde_json::Value::Null),
+            }
+            account
+        })
+        .collect();
+
+    Ok(ExportFile {
+        header: ExportHeader {
+            format: EXPORT_FORMAT.to_string(),
+            version: EXPORT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: chrono::Utc::now().timestamp_millis(),
+            account_count: accounts.len(),
+            options,
+        },
+        accounts,
+    })
+}
+
+/// 用密码加密导出内容
+pub fn seal_bundle(json: &str, passphrase: &str) -> Result<String, String> {
+    let envelope = VaultKey::generate(passphrase)?.seal_as(BUNDLE_FORMAT, json.as_bytes())?;
+    serde_json::to_string_pretty(&envelope)
+        .map_err(|e| format!("Failed to serialize export bundle: {}", e))
+}
+
+/// 如果是加密导出包则用密码解密，否则原样返回
+pub fn open_bundle(content: &str, passphrase: Option<&str>) -> Result<String, AppError> {
+    let Some(envelope) = vault::parse_envelope_as(content, BUNDLE_FORMAT) else {
+        return Ok(content.to_string());
+    };
+
+    let passphrase = passphrase
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| AppError::new(ErrorKind::PassphraseRequired, "This export is encrypted, a passphrase is required"))?;
+
+    let plaintext = VaultKey::derive(passphrase, &envelope.kdf)?
+        .open(&envelope)
+        .map_err(|e| AppError::new(ErrorKind::InvalidPassphrase, e))?;
+
+    String::from_utf8(plaintext).map_err(|e| AppError::from(format!("Invalid export bundle: {}", e)))
+}
+
+/// 解析导入数据：带 header 的导出文件或旧版纯账户数组
+pub fn parse_import(json: &str) -> Result<(Option<ExportHeader>, Vec<Account>), String> {
+    let value: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| format!("Failed to parse import data: {}", e))?;
+
+    if value.is_array() {
+        let accounts = serde_json::from_value(value)
+            .map_err(|e| format!("Failed to parse import data: {}", e))?;
+        return Ok((None, accounts));
+    }
+
+    let file: ExportFile = serde_json::from_value(value)
+        .map_err(|e| format!("Failed to parse import data: {}", e))?;
+    if file.header.format != EXPORT_FORMAT {
+        return Err(format!("Unsupported export format: {}", file.header.format));
+    }
+    if file.header.version > EXPORT_VERSION {
+        return Err(format!("Export version {} is newer than supported version {}", file.header.version, EXPORT_VERSION));
+    }
+    // 打码或仅元数据的导出不含可用的凭据，导入会覆盖已有的真实数据
+    if file.header.options.mode != ExportMode::Full {
+        return Err(format!(
+            "This export does not contain usable credentials (mode: {:?}) and cannot be imported",
+            file.header.options.mode
+        ));
+    }
+
+    Ok((Some(file.header), file.accounts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::test_support;
+
+    fn account(id: &str, platform: &str, email: &str) -> Account {
+        Account {
+            platform_data: PlatformData::Unknown(serde_json::json!({ "refresh_token": "1//0abcdefghijklmnopqrstuvwxyz" })),
+            ..test_support::account(id, platform, email)
+        }
+    }
+
+    #[test]
+    fn test_filter_and_redact() {
+        let accounts = vec![
+            account("a", "claude", "a@corp.com"),
+            account("b", "claude", "b@gmail.com"),
+            account("c", "kiro", "c@corp.com"),
+        ];
+        let options = ExportOptions {
+            mode: ExportMode::Redacted,
+            platforms: Some(vec!["claude".to_string()]),
+            email_pattern: Some("*@CORP.com".to_string()),
+            ..Default::default()
+        };
+
+        let file = build_export(&accounts, options).unwrap();
+        assert_eq!(file.header.account_count, 1);
+        assert_eq!(file.accounts[0].id, "a");
+        assert_eq!(file.accounts[0].platform_data.to_value()["refresh_token"], "1//0****wxyz");
+    }
+
+    #[test]
+    fn test_parse_import() {
+        let accounts = vec![account("a", "claude", "a@corp.com")];
+
+        let legacy = serde_json::to_string(&accounts).unwrap();
+        assert!(parse_import(&legacy).unwrap().0.is_none());
+
+        let full = serde_json::to_string(&build_export(&accounts, ExportOptions::default()).unwrap()).unwrap();
+        let (header, imported) = parse_import(&full).unwrap();
+        assert_eq!(header.unwrap().options.mode, ExportMode::Full);
+        assert_eq!(imported.len(), 1);
+
+        let metadata = ExportOptions { mode: ExportMode::Metadata, ..Default::default() };
+        let metadata = serde_json::to_string(&build_export(&accounts, metadata).unwrap()).unwrap();
+        assert!(parse_import(&metadata).is_err());
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let bundle = seal_bundle("[]", "usb stick").unwrap();
+        assert!(!bundle.contains("[]"));
+
+        assert_eq!(open_bundle(&bundle, None).unwrap_err().kind, ErrorKind::PassphraseRequired);
+        assert_eq!(open_bundle(&bundle, Some("wrong")).unwrap_err().kind, ErrorKind::InvalidPassphrase);
+        assert_eq!(open_bundle(&bundle, Some("usb stick")).unwrap(), "[]");
+        // 未加密的内容原样返回
+        assert_eq!(open_bundle("[]", None).unwrap(), "[]");
+    }
+}
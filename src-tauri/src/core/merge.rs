@@ -0,0 +1,270 @@
+//! 导入合并
+//!
+//! 按 id 和 (platform, email) 匹配已有账户，根据合并策略决定新增、覆盖或跳过，
+//! 并生成导入报告。不直接读写存储，dry-run 时在副本上执行即可。
+
+use serde::{Deserialize, Serialize};
+
+use super::export::ExportHeader;
+use super::storage::{Account, AccountSummary, Storage};
+
+/// 合并策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// 已存在则跳过
+    #[default]
+    Skip,
+    /// 用导入的数据覆盖
+    Overwrite,
+    /// 保留 last_used_at 较新的一方
+    KeepNewer,
+    /// 两者都保留，导入的账户使用新 id
+    KeepBoth,
+}
+
+/// 匹配方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    Id,
+    Email,
+}
+
+/// 导入报告中的一条记录
+#[derive(Debug, Serialize)]
+pub struct ImportEntry {
+    pub account: AccountSummary,
+    /// 匹配到的已有账户 id
+    pub matched_id: Option<String>,
+    pub matched_by: Option<MatchKind>,
+    pub reason: Option<String>,
+}
+
+/// 导入报告
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub strategy: MergeStrategy,
+    pub dry_run: bool,
+    /// 导入文件的 header，旧版纯数组导出为 None
+    pub source: Option<ExportHeader>,
+    pub added: Vec<ImportEntry>,
+    pub updated: Vec<ImportEntry>,
+    pub skipped: Vec<ImportEntry>,
+    /// id 和邮箱分别匹配到不同账户，无法自动处理
+    pub conflicts: Vec<ImportEntry>,
+}
+
+impl ImportReport {
+    /// 是否有需要保存的改动
+    pub fn has_changes(&self) -> bool {
+        !self.added.is_empty() || !self.updated.is_empty()
+    }
+}
+
+fn same_identity(a: &Account, b: &Account) -> bool {
+    a.platform == b.platform && a.email.eq_ignore_ascii_case(&b.email)
+}
+
+fn entry(account: &Account, matched: Option<(&str, MatchKind)>, reason: Option<&str>) -> ImportEntry {
+    ImportEntry {
+        account: account.into(),
+        matched_id: matched.map(|(id, _)| id.to_string()),
+        matched_by: matched.map(|(_, kind)| kind),
+        reason: reason.map(str::to_string),
+    }
+}
+
+/// 将导入的账户合并到已有列表
+pub fn merge_accounts(existing: &mut Vec<Account>, incoming: Vec<Account>, strategy: MergeStrategy) -> ImportReport {
+    let mut report = ImportReport { strategy, ..Default::default() };
+    let now = chrono::Utc::now().timestamp_millis();
+
+    for mut account in incoming {
+        let by_id = existing.iter().position(|a| a.id == account.id);
+        let by_email = existing.iter().position(|a| same_identity(a, &account));
+
+        let (index, kind) = match (by_id, by_email) {
+            (Some(i), Some(e)) if i != e => {
+                report.conflicts.push(ImportEntry {
+                    reason: Some(format!(
+                        "id matches account {} but email matches account {}",
+                        existing[i].id, existing[e].id
+                    )),
+                    ..entry(&account, Some((&existing[i].id, MatchKind::Id)), None)
+                });
+                continue;
+            }
+            (Some(i), _) => (i, MatchKind::Id),
+            (None, Some(e)) => (e, MatchKind::Email),
+            (None, None) => {
+                // 导入的账户不会改变当前激活账户
+                account.is_active = false;
+                account.updated_at = now;
+                report.added.push(entry(&account, None, None));
+                existing.push(account);
+                continue;
+            }
+        };
+
+        let current = existing[index].clone();
+        let matched = Some((current.id.as_str(), kind));
+
+        let identical = serde_json::to_value(&current).ok() == serde_json::to_value(&account).ok();
+        if identical {
+            report.skipped.push(entry(&account, matched, Some("identical")));
+            continue;
+        }
+
+        let replace = match strategy {
+            MergeStrategy::Skip => {
+                report.skipped.push(entry(&account, matched, Some("already exists")));
+                false
+            }
+            MergeStrategy::Overwrite => true,
+            MergeStrategy::KeepNewer if account.last_used_at > current.last_used_at => true,
+            MergeStrategy::KeepNewer => {
+                report.skipped.push(entry(&account, matched, Some("existing account is newer")));
+                false
+            }
+            MergeStrategy::KeepBoth => {
+                let mut copy = account.clone();
+                copy.id = uuid::Uuid::new_v4().to_string();
+                copy.is_active = false;
+                copy.updated_at = now;
+                report.added.push(entry(&copy, matched, Some("kept both")));
+                existing.push(copy);
+                false
+            }
+        };
+
+        if replace {
+            // 保留原 id（机器码绑定以 id 为键）和激活状态
+            account.id = current.id.clone();
+            account.is_active = current.is_active;
+            account.updated_at = now;
+            report.updated.push(entry(&account, matched, None));
+            existing[index] = account;
+        }
+    }
+
+    report
+}
+
+/// 将另一份存储合并到 target（切换存储位置时目标已有数据）：账户按 merge_accounts 处理，
+/// 回收站和切换历史取并集，机器码绑定只补充 target 中尚未绑定的账户
+pub fn merge_storage(target: &mut Storage, other: &Storage, strategy: MergeStrategy) -> ImportReport {
+    let report = merge_accounts(&mut target.accounts, other.accounts.clone(), strategy);
+
+    if target.machine_id.is_none() {
+        target.machine_id = other.machine_id.clone();
+    }
+    for (id, machine_id) in &other.account_machine_bindings {
+        if target.accounts.iter().any(|a| &a.id == id) {
+            target.account_machine_bindings.entry(id.clone()).or_insert_with(|| machine_id.clone());
+        }
+    }
+
+    for deleted in &other.trash {
+        let known = target.accounts.iter().any(|a| a.id == deleted.account.id)
+            || target.trash.iter().any(|d| d.account.id == deleted.account.id);
+        if !known {
+            target.trash.push(deleted.clone());
+        }
+    }
+
+    for record in &other.switch_history {
+        let exists = target.switch_history.iter().any(|r| {
+            r.platform == record.platform && r.account_id == record.account_id && r.switched_at == record.switched_at
+        });
+        if !exists {
+            target.switch_history.push(record.clone());
+        }
+    }
+    target.switch_history.sort_by_key(|r| r.switched_at);
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support;
+
+    fn account(id: &str, email: &str, last_used_at: i64) -> Account {
+        Account { last_used_at, ..test_support::account(id, "claude", email) }
+    }
+
+    #[test]
+    fn test_reimport_is_idempotent() {
+        let mut existing = vec![account("a", "a@example.com", 1)];
+        let report = merge_accounts(&mut existing, vec![account("a", "a@example.com", 1)], MergeStrategy::Skip);
+
+        assert_eq!(existing.len(), 1);
+        assert!(!report.has_changes());
+        assert_eq!(report.skipped[0].reason.as_deref(), Some("identical"));
+    }
+
+    #[test]
+    fn test_strategies() {
+        let base = || vec![account("a", "a@example.com", 10)];
+
+        // 按邮箱匹配，覆盖时保留原 id
+        let mut existing = base();
+        let report = merge_accounts(&mut existing, vec![account("x", "A@example.com", 5)], MergeStrategy::Overwrite);
+        assert_eq!(report.updated[0].matched_by, Some(MatchKind::Email));
+        assert_eq!(existing.len(), 1);
+        assert_eq!(existing[0].id, "a");
+        assert_eq!(existing[0].last_used_at, 5);
+
+        let mut existing = base();
+        let report = merge_accounts(&mut existing, vec![account("a", "a@example.com", 5)], MergeStrategy::KeepNewer);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(existing[0].last_used_at, 10);
+
+        let mut existing = base();
+        merge_accounts(&mut existing, vec![account("a", "a@example.com", 20)], MergeStrategy::KeepNewer);
+        assert_eq!(existing[0].last_used_at, 20);
+
+        let mut existing = base();
+        let report = merge_accounts(&mut existing, vec![account("a", "a@example.com", 5)], MergeStrategy::KeepBoth);
+        assert_eq!(existing.len(), 2);
+        assert_ne!(existing[1].id, "a");
+        assert_eq!(report.added.len(), 1);
+    }
+
+    #[test]
+    fn test_conflict() {
+        let mut existing = vec![account("a", "a@example.com", 0), account("b", "b@example.com", 0)];
+        let report = merge_accounts(&mut existing, vec![account("a", "b@example.com", 0)], MergeStrategy::Overwrite);
+
+        assert_eq!(report.conflicts.len(), 1);
+        assert!(!report.has_changes());
+        assert_eq!(existing[0].email, "a@example.com");
+    }
+
+    #[test]
+    fn test_merge_storage() {
+        let mut target = Storage::new();
+        target.accounts = vec![account("a", "a@example.com", 10)];
+        target.account_machine_bindings.insert("a".to_string(), "m-target".to_string());
+
+        let mut other = Storage::new();
+        other.machine_id = Some("machine".to_string());
+        other.accounts = vec![account("x", "A@example.com", 20), account("b", "b@example.com", 0)];
+        other.account_machine_bindings.insert("a".to_string(), "m-other".to_string());
+        other.account_machine_bindings.insert("b".to_string(), "m-b".to_string());
+        other.trash.push(crate::core::storage::DeletedAccount { account: account("c", "c@example.com", 0), deleted_at: 1 });
+
+        let report = merge_storage(&mut target, &other, MergeStrategy::KeepNewer);
+        assert_eq!(report.updated.len(), 1);
+        assert_eq!(report.added.len(), 1);
+        let ids: Vec<_> = target.accounts.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+        assert_eq!(target.accounts[0].last_used_at, 20);
+        assert_eq!(target.account_machine_bindings.get("a").map(String::as_str), Some("m-target"));
+        assert_eq!(target.account_machine_bindings.get("b").map(String::as_str), Some("m-b"));
+        assert_eq!(target.machine_id.as_deref(), Some("machine"));
+        assert_eq!(target.trash.len(), 1);
+    }
+}
@@ -0,0 +1,154 @@
+//! Storage 数据结构版本与迁移
+//!
+//! accounts.json 顶层带 `version` 字段（缺失视为 0）。加载时在反序列化之前，
+//! 按顺序执行迁移函数把 JSON 升级到当前版本；新增字段时追加一个迁移即可。
+
+use serde_json::{json, Value};
+
+/// 当前 Storage 结构版本
+pub const CURRENT_SCHEMA_VERSION: u32 = 5;
+
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+/// 迁移链：下标 i 的函数把版本 i 升级到 i + 1
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1, migrate_v1_to_v2, migrate_v2_to_v3, migrate_v3_to_v4, migrate_v4_to_v5];
+
+/// 迁移结果
+#[derive(Debug, PartialEq, Eq)]
+pub enum MigrationOutcome {
+    /// 已是当前版本
+    Current,
+    /// 从指定版本迁移到了当前版本
+    Migrated { from: u32 },
+    /// 文件由更新的版本写入，不做任何修改
+    Newer { version: u32 },
+}
+
+/// 读取 JSON 中的结构版本
+pub fn schema_version(value: &Value) -> u32 {
+    value.get("version")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .unwrap_or(0)
+}
+
+/// 将 JSON 迁移到当前版本
+pub fn migrate(value: &mut Value) -> Result<MigrationOutcome, String> {
+    if !value.is_object() {
+        return Err("Storage root must be a JSON object".to_string());
+    }
+
+    let from = schema_version(value);
+    if from > CURRENT_SCHEMA_VERSION {
+        return Ok(MigrationOutcome::Newer { version: from });
+    }
+    if from == CURRENT_SCHEMA_VERSION {
+        return Ok(MigrationOutcome::Current);
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        migration(value).map_err(|e| format!("Migration to version {} failed: {}", index + 1, e))?;
+        value["version"] = json!(index + 1);
+    }
+
+    Ok(MigrationOutcome::Migrated { from })
+}
+
+/// v0 -> v1：引入 version 字段，补齐早期文件可能缺失的顶层字段
+fn migrate_v0_to_v1(value: &mut Value) -> Result<(), String> {
+    let obj = value.as_object_mut().ok_or("Storage root must be a JSON object")?;
+
+    obj.entry("accounts").or_insert_with(|| json!([]));
+    obj.entry("machine_id").or_insert(Value::Null);
+    obj.entry("account_machine_bindings").or_insert_with(|| json!({}));
+
+    Ok(())
+}
+
+/// v1 -> v2：新增回收站
+fn migrate_v1_to_v2(value: &mut Value) -> Result<(), String> {
+    let obj = value.as_object_mut().ok_or("Storage root must be a JSON object")?;
+
+    obj.entry("trash").or_insert_with(|| json!([]));
+
+    Ok(())
+}
+
+/// v2 -> v3：账户新增标签、分组、备注和自定义字段
+fn migrate_v2_to_v3(value: &mut Value) -> Result<(), String> {
+    let obj = value.as_object_mut().ok_or("Storage root must be a JSON object")?;
+
+    let accounts = obj.get_mut("accounts")
+        .and_then(|a| a.as_array_mut())
+        .ok_or("accounts must be an array")?;
+    for account in accounts.iter_mut().filter_map(|a| a.as_object_mut()) {
+        account.entry("tags").or_insert_with(|| json!([]));
+        account.entry("group").or_insert(Value::Null);
+        account.entry("notes").or_insert(Value::Null);
+        account.entry("custom_fields").or_insert_with(|| json!({}));
+    }
+
+    Ok(())
+}
+
+/// v3 -> v4：新增账号切换历史
+fn migrate_v3_to_v4(value: &mut Value) -> Result<(), String> {
+    let obj = value.as_object_mut().ok_or("Storage root must be a JSON object")?;
+
+    obj.entry("switch_history").or_insert_with(|| json!([]));
+
+    Ok(())
+}
+
+/// v4 -> v5：账户新增修改时间，取创建时间和最近使用时间中较新的一个
+fn migrate_v4_to_v5(value: &mut Value) -> Result<(), String> {
+    let obj = value.as_object_mut().ok_or("Storage root must be a JSON object")?;
+
+    let accounts = obj.get_mut("accounts")
+        .and_then(|a| a.as_array_mut())
+        .ok_or("accounts must be an array")?;
+    for account in accounts.iter_mut().filter_map(|a| a.as_object_mut()) {
+        let timestamp = |key: &str| account.get(key).and_then(Value::as_i64).unwrap_or(0);
+        let updated_at = timestamp("created_at").max(timestamp("last_used_at"));
+        account.entry("updated_at").or_insert_with(|| json!(updated_at));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_unversioned() {
+        let mut value = json!({ "accounts": [{ "id": "a" }] });
+        let outcome = migrate(&mut value).unwrap();
+
+        assert_eq!(outcome, MigrationOutcome::Migrated { from: 0 });
+        assert_eq!(schema_version(&value), CURRENT_SCHEMA_VERSION);
+        assert_eq!(value["account_machine_bindings"], json!({}));
+        assert_eq!(value["trash"], json!([]));
+        assert_eq!(value["accounts"][0]["tags"], json!([]));
+        assert_eq!(value["accounts"][0]["updated_at"], json!(0));
+    }
+
+    #[test]
+    fn test_current_and_newer_untouched() {
+        let mut current = json!({ "version": CURRENT_SCHEMA_VERSION, "accounts": [] });
+        assert_eq!(migrate(&mut current).unwrap(), MigrationOutcome::Current);
+
+        let mut newer = json!({ "version": CURRENT_SCHEMA_VERSION + 1, "accounts": [], "future": true });
+        let before = newer.clone();
+        assert_eq!(
+            migrate(&mut newer).unwrap(),
+            MigrationOutcome::Newer { version: CURRENT_SCHEMA_VERSION + 1 }
+        );
+        assert_eq!(newer, before);
+    }
+
+    #[test]
+    fn test_rejects_non_object() {
+        assert!(migrate(&mut json!([])).is_err());
+    }
+}
@@ -3,5 +3,24 @@ pub mod oauth;
 pub mod oauth_server;
 pub mod quota;
 pub mod kiro;
+pub mod vault;
+pub mod error;
+pub mod sqlite;
+pub mod migration;
+pub mod recovery;
+pub mod snapshot;
+pub mod merge;
+pub mod export;
+pub mod platform;
+pub mod query;
+pub mod audit;
+pub mod consistency;
+pub mod watcher;
+pub mod vault_registry;
+pub mod relocate;
+pub mod settings;
+pub mod secret_store;
+#[cfg(test)]
+pub(crate) mod test_support;
 
 pub use storage::*;
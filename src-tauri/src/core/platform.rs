@@ -0,0 +1,412 @@
+//! 各平台的账户数据（Account.platform_data）
+//!
+//! 按 `Account.platform` 解析为对应平台的结构体，凭据字段有明确类型。
+//! 未声明的字段保存在 `extra` 中原样写回，保证新版本前端写入的字段不会丢失。
+//! 从存储加载时宽松解析（无法识别的数据保留为 `Unknown`），
+//! add_account / update_account 时通过 `validate` 严格校验：必填凭据不能为空，
+//! 未声明字段中不能有疑似拼写错误的字段名，环境变量等映射中的已知键必须是对应的 JSON 类型。
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// 平台账户数据
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum PlatformData {
+    Antigravity(AntigravityData),
+    Kiro(KiroData),
+    Claude(ClaudeData),
+    Codex(CodexData),
+    Gemini(GeminiData),
+    /// 未知平台、锁定/导出时去掉的数据（Null），或无法通过校验的旧数据
+    Unknown(Value),
+}
+
+// ==================== Antigravity ====================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AntigravityData {
+    pub token: AntigravityToken,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_forbidden: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_profile: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AntigravityToken {
+    pub access_token: String,
+    pub refresh_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_in: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry_timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+// ==================== Kiro ====================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KiroData {
+    pub credentials: KiroCredentials,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KiroCredentials {
+    pub access_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+// ==================== Claude / Codex / Gemini ====================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeData {
+    pub config: ClaudeConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClaudeConfig {
+    /// 写入 ~/.claude/settings.json 的环境变量（ANTHROPIC_API_KEY 等）
+    pub env: Map<String, Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexData {
+    pub config: CodexConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodexConfig {
+    /// ~/.codex/auth.json 内容
+    pub auth: Map<String, Value>,
+    /// ~/.codex/config.toml 内容
+    pub config: Map<String, Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiData {
+    pub config: GeminiConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeminiConfig {
+    /// ~/.gemini/.env 中的变量
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<Map<String, Value>>,
+    #[serde(default)]
+    pub config: Map<String, Value>,
+    #[serde(default)]
+    pub settings: Map<String, Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for PlatformData {
+    fn default() -> Self {
+        Self::Unknown(Value::Null)
+    }
+}
+
+impl PlatformData {
+    /// 严格解析：已知平台的数据必须符合对应结构
+    pub fn parse(platform: &str, value: Value) -> Result<Self, String> {
+        fn typed<T: DeserializeOwned>(platform: &str, value: Value) -> Result<T, String> {
+            serde_json::from_value(value)
+                .map_err(|e| format!("Invalid {} account data: {}", platform, e))
+        }
+
+        Ok(match platform {
+            "antigravity" => Self::Antigravity(typed(platform, value)?),
+            "kiro" => Self::Kiro(typed(platform, value)?),
+            "claude" => Self::Claude(typed(platform, value)?),
+            "codex" => Self::Codex(typed(platform, value)?),
+            "gemini" => Self::Gemini(typed(platform, value)?),
+            _ => Self::Unknown(value),
+        })
+    }
+
+    /// 宽松解析：用于从存储加载，解析失败时原样保留
+    pub fn parse_lenient(platform: &str, value: Value) -> Self {
+        if value.is_null() {
+            return Self::Unknown(value);
+        }
+        Self::parse(platform, value.clone()).unwrap_or(Self::Unknown(value))
+    }
+
+    /// 转换为 JSON
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    /// 校验数据与平台匹配、必填凭据不为空，并拒绝疑似拼写错误的字段和类型错误的已知键
+    pub fn validate(&self, platform: &str) -> Result<(), String> {
+        const PROVIDER: &[&str] = &["config", "providerId"];
+
+        // 每一层的未声明字段及该层已声明的字段名
+        let levels: Vec<(&Map<String, Value>, &[&str])> = match self {
+            Self::Antigravity(d) => vec![
+                (&d.extra, &["token", "quota", "is_forbidden", "proxy_id", "device_profile"]),
+                (&d.token.extra, &["access_token", "refresh_token", "expires_in", "expiry_timestamp", "token_type", "session_id"]),
+            ],
+            Self::Kiro(d) => vec![
+                (&d.extra, &["credentials", "idp", "userId", "machineId", "subscription", "usage", "status"]),
+                (&d.credentials.extra, &["accessToken", "refreshToken", "clientId", "clientSecret", "region", "expiresAt", "authMethod", "provider"]),
+            ],
+            Self::Claude(d) => vec![(&d.extra, PROVIDER), (&d.config.extra, &["env"])],
+            Self::Codex(d) => vec![(&d.extra, PROVIDER), (&d.config.extra, &["auth", "config"])],
+            Self::Gemini(d) => vec![(&d.extra, PROVIDER), (&d.config.extra, &["env", "config", "settings"])],
+            // 已知平台却无法识别：重新严格解析以给出具体错误
+            Self::Unknown(value) => return Self::parse(platform, value.clone()).map(|_| ()),
+        };
+
+        if self.platform() != Some(platform) {
+            return Err(format!("Account data does not match platform {}", platform));
+        }
+
+        // 必填凭据：结构体保证字段存在，这里再拒绝空字符串。
+        // Claude / Codex / Gemini 的必填部分是 config 及其中的映射，解析时已保证存在
+        let required: Vec<(&str, &str)> = match self {
+            Self::Antigravity(d) => vec![
+                ("token.access_token", &d.token.access_token),
+                ("token.refresh_token", &d.token.refresh_token),
+            ],
+            Self::Kiro(d) => vec![("credentials.accessToken", &d.credentials.access_token)],
+            _ => vec![],
+        };
+        for (field, value) in required {
+            if value.trim().is_empty() {
+                return Err(format!("Missing required field \"{}\"", field));
+            }
+        }
+
+        for (extra, known) in levels {
+            for key in extra.keys() {
+                if let Some(field) = known.iter().find(|field| normalize(field) == normalize(key)) {
+                    return Err(format!("Unknown field \"{}\", did you mean \"{}\"?", key, field));
+                }
+            }
+        }
+
+        // 映射中的已知键（null 视为未设置）
+        let maps: Vec<(&Map<String, Value>, KnownKeys)> = match self {
+            Self::Claude(d) => vec![(&d.config.env, CLAUDE_ENV)],
+            Self::Codex(d) => vec![(&d.config.auth, CODEX_AUTH), (&d.config.config, CODEX_CONFIG)],
+            Self::Gemini(d) => d.config.env.iter().map(|env| (env, GEMINI_ENV)).collect(),
+            _ => vec![],
+        };
+        for (map, known) in maps {
+            for (key, kind) in known {
+                match map.get(*key) {
+                    Some(value) if !value.is_null() && !kind.matches(value) => {
+                        return Err(format!("Field \"{}\" must be {}", key, kind.name()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 数据对应的平台，Unknown 返回 None
+    pub fn platform(&self) -> Option<&'static str> {
+        match self {
+            Self::Antigravity(_) => Some("antigravity"),
+            Self::Kiro(_) => Some("kiro"),
+            Self::Claude(_) => Some("claude"),
+            Self::Codex(_) => Some("codex"),
+            Self::Gemini(_) => Some("gemini"),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+/// 已知键期望的 JSON 类型
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    String,
+    Bool,
+    Object,
+}
+
+impl Kind {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            Self::String => value.is_string(),
+            Self::Bool => value.is_boolean(),
+            Self::Object => value.is_object(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::String => "a string",
+            Self::Bool => "a boolean",
+            Self::Object => "an object",
+        }
+    }
+}
+
+/// 映射中的已知键及其类型
+type KnownKeys = &'static [(&'static str, Kind)];
+
+/// ~/.claude/settings.json 的 env
+const CLAUDE_ENV: KnownKeys = &[
+    ("ANTHROPIC_API_KEY", Kind::String),
+    ("ANTHROPIC_AUTH_TOKEN", Kind::String),
+    ("ANTHROPIC_BASE_URL", Kind::String),
+    ("ANTHROPIC_MODEL", Kind::String),
+    ("ANTHROPIC_REASONING_MODEL", Kind::String),
+    ("ANTHROPIC_DEFAULT_HAIKU_MODEL", Kind::String),
+    ("ANTHROPIC_DEFAULT_SONNET_MODEL", Kind::String),
+    ("ANTHROPIC_DEFAULT_OPUS_MODEL", Kind::String),
+];
+
+/// ~/.codex/auth.json
+const CODEX_AUTH: KnownKeys = &[("OPENAI_API_KEY", Kind::String)];
+
+/// ~/.codex/config.toml
+const CODEX_CONFIG: KnownKeys = &[
+    ("model_provider", Kind::String),
+    ("model", Kind::String),
+    ("model_reasoning_effort", Kind::String),
+    ("disable_response_storage", Kind::Bool),
+    ("model_providers", Kind::Object),
+];
+
+/// ~/.gemini/.env
+const GEMINI_ENV: KnownKeys = &[
+    ("GOOGLE_GEMINI_BASE_URL", Kind::String),
+    ("GEMINI_API_KEY", Kind::String),
+    ("GOOGLE_API_KEY", Kind::String),
+    ("GEMINI_MODEL", Kind::String),
+];
+
+/// 字段名归一化：忽略大小写、下划线和连字符
+fn normalize(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .collect::<String>()
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_typed_round_trip_keeps_unknown_fields() {
+        let value = json!({
+            "credentials": { "accessToken": "at", "refreshToken": "rt", "futureField": 1 },
+            "idp": "Github",
+            "tags": ["work"]
+        });
+
+        let data = PlatformData::parse("kiro", value.clone()).unwrap();
+        assert!(matches!(&data, PlatformData::Kiro(d) if d.credentials.refresh_token.as_deref() == Some("rt")));
+        assert_eq!(data.to_value(), value);
+        assert!(data.validate("kiro").is_ok());
+    }
+
+    #[test]
+    fn test_validation() {
+        // 缺少必填凭据
+        assert!(PlatformData::parse("antigravity", json!({ "token": { "access_token": "a" } })).is_err());
+        // 类型错误
+        assert!(PlatformData::parse("claude", json!({ "config": { "env": "x" } })).is_err());
+
+        // 疑似拼写错误
+        let data = PlatformData::parse("claude", json!({ "config": { "env": {} }, "provider_id": "p" })).unwrap();
+        assert!(data.validate("claude").is_err());
+
+        // 与平台不匹配
+        let data = PlatformData::parse("codex", json!({ "config": { "auth": {}, "config": {} } })).unwrap();
+        assert!(data.validate("gemini").is_err());
+
+        // 必填凭据为空
+        let data = PlatformData::parse("kiro", json!({ "credentials": { "accessToken": " " } })).unwrap();
+        assert!(data.validate("kiro").is_err());
+
+        // 映射中的已知键类型错误，null 视为未设置
+        let data = PlatformData::parse("claude", json!({ "config": { "env": { "ANTHROPIC_API_KEY": 123 } } })).unwrap();
+        assert!(data.validate("claude").is_err());
+        let data = PlatformData::parse("codex", json!({
+            "config": { "auth": { "OPENAI_API_KEY": null }, "config": { "disable_response_storage": "yes" } }
+        })).unwrap();
+        assert_eq!(data.validate("codex").unwrap_err(), "Field \"disable_response_storage\" must be a boolean");
+        let data = PlatformData::parse("gemini", json!({ "config": { "env": { "GEMINI_API_KEY": "k", "CUSTOM": 1 } } })).unwrap();
+        assert!(data.validate("gemini").is_ok());
+    }
+
+    #[test]
+    fn test_lenient_parse() {
+        let broken = json!({ "token": "not-an-object" });
+        assert_eq!(PlatformData::parse_lenient("antigravity", broken.clone()), PlatformData::Unknown(broken.clone()));
+        assert!(PlatformData::Unknown(broken).validate("antigravity").is_err());
+
+        let other = json!({ "anything": true });
+        assert!(PlatformData::Unknown(other).validate("cursor").is_ok());
+    }
+}
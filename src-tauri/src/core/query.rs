@@ -0,0 +1,253 @@
+//! 账户查询
+//!
+//! 在后端完成筛选和排序，避免在前端处理大量账户
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+use super::platform::PlatformData;
+use super::storage::Account;
+
+/// 额度低于该剩余百分比视为“额度不足”
+const LOW_QUOTA_PERCENT: f64 = 20.0;
+
+/// 额度状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaState {
+    Available,
+    Low,
+    Exhausted,
+    /// 账户被封禁或无权限
+    Forbidden,
+    /// 平台没有额度信息，或尚未查询
+    Unknown,
+}
+
+impl QuotaState {
+    /// 按额度排序时的序号：额度由多到少，Unknown 没有序号，与空值一样排在最后
+    pub fn ordinal(self) -> Option<u8> {
+        match self {
+            QuotaState::Available => Some(0),
+            QuotaState::Low => Some(1),
+            QuotaState::Exhausted => Some(2),
+            QuotaState::Forbidden => Some(3),
+            QuotaState::Unknown => None,
+        }
+    }
+}
+
+/// 查询条件，未设置的条件不参与筛选
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AccountQuery {
+    pub platform: Option<String>,
+    /// 必须同时包含的标签
+    pub tags: Vec<String>,
+    pub group: Option<String>,
+    pub is_active: Option<bool>,
+    pub quota: Option<QuotaState>,
+    /// 邮箱子串，忽略大小写
+    pub email: Option<String>,
+    /// 排序字段：账户的任意顶层字段，`custom_fields.<key>`，或按额度状态排序的 `quota`。
+    /// 无论升序降序，没有该字段的账户都排在最后
+    pub sort_by: Option<String>,
+    pub descending: bool,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// 根据平台数据计算额度状态
+pub fn quota_state(account: &Account) -> QuotaState {
+    match &account.platform_data {
+        PlatformData::Antigravity(data) => {
+            let quota = data.quota.as_ref();
+            let forbidden = data.is_forbidden.unwrap_or(false)
+                || quota.and_then(|q| q["is_forbidden"].as_bool()).unwrap_or(false);
+            if forbidden {
+                return QuotaState::Forbidden;
+            }
+
+            // percentage 为各模型的剩余百分比，取最低的一项
+            let remaining = quota
+                .and_then(|q| q["models"].as_array())
+                .and_then(|models| {
+                    models.iter()
+                        .filter_map(|m| m["percentage"].as_f64())
+                        .min_by(|a, b| a.total_cmp(b))
+                });
+            match remaining {
+                Some(r) => state_from_remaining(r),
+                None => QuotaState::Unknown,
+            }
+        }
+        PlatformData::Kiro(data) => {
+            if data.status.as_deref() == Some("banned") {
+                return QuotaState::Forbidden;
+            }
+
+            let usage = data.usage.as_ref();
+            let current = usage.and_then(|u| u["current"].as_f64());
+            let limit = usage.and_then(|u| u["limit"].as_f64()).filter(|l| *l > 0.0);
+            match (current, limit) {
+                (Some(current), Some(limit)) => state_from_remaining((1.0 - current / limit) * 100.0),
+                _ => QuotaState::Unknown,
+            }
+        }
+        _ => QuotaState::Unknown,
+    }
+}
+
+fn state_from_remaining(percent: f64) -> QuotaState {
+    if percent <= 0.0 {
+        QuotaState::Exhausted
+    } else if percent < LOW_QUOTA_PERCENT {
+        QuotaState::Low
+    } else {
+        QuotaState::Available
+    }
+}
+
+impl AccountQuery {
+    pub fn matches(&self, account: &Account) -> bool {
+        self.platform.as_ref().is_none_or(|p| &account.platform == p)
+            && self.tags.iter().all(|tag| account.tags.contains(tag))
+            && self.group.as_ref().is_none_or(|g| account.group.as_ref() == Some(g))
+            && self.is_active.is_none_or(|active| account.is_active == active)
+            && self.quota.is_none_or(|state| quota_state(account) == state)
+            && self.email.as_ref().is_none_or(|needle| {
+                account.email.to_lowercase().contains(&needle.to_lowercase())
+            })
+    }
+
+    /// 执行查询：筛选、排序、分页
+    pub fn run(&self, accounts: &[Account]) -> Vec<Account> {
+        let mut result: Vec<Account> = accounts.iter()
+            .filter(|a| self.matches(a))
+            .cloned()
+            .collect();
+
+        if let Some(field) = &self.sort_by {
+            // 预先取出排序键，避免在比较时反复序列化
+            let mut keyed: Vec<(Value, Account)> = result.into_iter()
+                .map(|account| (sort_key(&account, field), account))
+                .collect();
+            keyed.sort_by(|(a, _), (b, _)| compare_values(a, b, self.descending));
+            result = keyed.into_iter().map(|(_, account)| account).collect();
+        }
+
+        result.into_iter()
+            .skip(self.offset.unwrap_or(0))
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+}
+
+fn sort_key(account: &Account, field: &str) -> Value {
+    if field == "quota" {
+        return quota_state(account).ordinal().map(Value::from).unwrap_or(Value::Null);
+    }
+    if let Some(key) = field.strip_prefix("custom_fields.") {
+        return account.custom_fields.get(key).cloned().map(Value::String).unwrap_or(Value::Null);
+    }
+    serde_json::to_value(account)
+        .ok()
+        .and_then(|mut value| value.get_mut(field).map(Value::take))
+        .unwrap_or(Value::Null)
+}
+
+/// 比较两个 JSON 值：数字按大小，字符串忽略大小写；descending 只反转非空值的顺序，空值始终排在最后
+fn compare_values(a: &Value, b: &Value, descending: bool) -> Ordering {
+    let ordering = match (a, b) {
+        (Value::Null, Value::Null) => return Ordering::Equal,
+        (Value::Null, _) => return Ordering::Greater,
+        (_, Value::Null) => return Ordering::Less,
+        (Value::Number(a), Value::Number(b)) => {
+            a.as_f64().unwrap_or(0.0).total_cmp(&b.as_f64().unwrap_or(0.0))
+        }
+        (Value::String(a), Value::String(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        _ => a.to_string().cmp(&b.to_string()),
+    };
+    if descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::account_with_data as account;
+    use serde_json::json;
+
+    #[test]
+    fn test_quota_state() {
+        let token = json!({ "access_token": "a", "refresh_token": "r" });
+        let exhausted = account("a", "antigravity", "a@x.com", json!({
+            "token": token,
+            "quota": { "models": [{ "percentage": 80 }, { "percentage": 0 }], "is_forbidden": false }
+        }));
+        assert_eq!(quota_state(&exhausted), QuotaState::Exhausted);
+
+        let low = account("b", "kiro", "b@x.com", json!({
+            "credentials": { "accessToken": "a" },
+            "usage": { "current": 45, "limit": 50 }
+        }));
+        assert_eq!(quota_state(&low), QuotaState::Low);
+
+        let claude = account("c", "claude", "c@x.com", json!({ "config": { "env": {} } }));
+        assert_eq!(quota_state(&claude), QuotaState::Unknown);
+    }
+
+    #[test]
+    fn test_filter_and_sort() {
+        let mut a = account("a", "claude", "Alice@corp.com", json!({ "config": { "env": {} } }));
+        a.tags = vec!["work".to_string()];
+        a.custom_fields.insert("seat".to_string(), "2".to_string());
+        let mut b = account("b", "claude", "bob@corp.com", json!({ "config": { "env": {} } }));
+        b.tags = vec!["work".to_string(), "shared".to_string()];
+        b.custom_fields.insert("seat".to_string(), "1".to_string());
+        let c = account("c", "codex", "carol@home.com", json!({}));
+        let accounts = vec![a, b, c];
+
+        let query = AccountQuery {
+            tags: vec!["work".to_string()],
+            email: Some("CORP".to_string()),
+            sort_by: Some("custom_fields.seat".to_string()),
+            ..Default::default()
+        };
+        let ids: Vec<_> = query.run(&accounts).into_iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec!["b", "a"]);
+
+        let query = AccountQuery { sort_by: Some("email".to_string()), descending: true, limit: Some(1), ..Default::default() };
+        assert_eq!(query.run(&accounts)[0].id, "c");
+
+        // 降序时没有该字段的账户仍排在最后
+        let query = AccountQuery { sort_by: Some("custom_fields.seat".to_string()), descending: true, ..Default::default() };
+        let ids: Vec<_> = query.run(&accounts).into_iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_sort_by_quota() {
+        let token = json!({ "access_token": "a", "refresh_token": "r" });
+        let quota = |percentage: u32| json!({ "token": token, "quota": { "models": [{ "percentage": percentage }] } });
+        let accounts = vec![
+            account("unknown", "claude", "u@x.com", json!({ "config": { "env": {} } })),
+            account("exhausted", "antigravity", "e@x.com", quota(0)),
+            account("available", "antigravity", "a@x.com", quota(90)),
+            account("low", "antigravity", "l@x.com", quota(10)),
+        ];
+
+        let query = AccountQuery { sort_by: Some("quota".to_string()), ..Default::default() };
+        let ids: Vec<_> = query.run(&accounts).into_iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec!["available", "low", "exhausted", "unknown"]);
+
+        let query = AccountQuery { sort_by: Some("quota".to_string()), descending: true, ..Default::default() };
+        let ids: Vec<_> = query.run(&accounts).into_iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec!["exhausted", "low", "available", "unknown"]);
+    }
+}
@@ -0,0 +1,81 @@
+//! 存储损坏恢复
+//!
+//! accounts.json 无法解析时，将其移动为带时间戳的 `.corrupt` 副本，
+//! 再从最新的可用备份恢复，并通过 `storage-recovered` 事件通知前端。
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 恢复事件名称
+pub const STORAGE_RECOVERED_EVENT: &str = "storage-recovered";
+
+/// 恢复报告
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryReport {
+    /// 损坏文件被移动到的位置
+    pub corrupt_path: String,
+    /// 用于恢复的备份文件，None 表示没有可用备份
+    pub restored_from: Option<String>,
+    /// 解析失败的原因
+    pub error: String,
+    pub recovered_at: i64,
+}
+
+/// 最近一次恢复报告（启动时前端尚未监听事件，可通过命令查询）
+static LAST_RECOVERY: Lazy<Mutex<Option<RecoveryReport>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn set_last_recovery(report: RecoveryReport) {
+    if let Ok(mut last) = LAST_RECOVERY.lock() {
+        *last = Some(report);
+    }
+}
+
+/// 将损坏文件移动为 `accounts.json.corrupt-YYYYmmdd-HHMMSS`
+pub fn quarantine(path: &Path) -> Result<PathBuf, String> {
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let file_name = path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("accounts.json");
+    let corrupt_path = path.with_file_name(format!("{}.corrupt-{}", file_name, timestamp));
+
+    fs::rename(path, &corrupt_path)
+        .map_err(|e| format!("Failed to move corrupt storage aside: {}", e))?;
+
+    Ok(corrupt_path)
+}
+
+/// 查找备份文件（`accounts.json.bak`、`accounts.json.v0.bak` 等），按修改时间从新到旧排序
+pub fn find_backups(path: &Path) -> Vec<PathBuf> {
+    let (Some(dir), Some(file_name)) = (path.parent(), path.file_name().and_then(|n| n.to_str())) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.", file_name);
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with(&prefix) && name.ends_with(".bak")
+        })
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect();
+
+    backups.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    backups.into_iter().map(|(_, path)| path).collect()
+}
+
+/// 获取最近一次恢复报告
+#[tauri::command]
+pub fn get_storage_recovery_report() -> Option<RecoveryReport> {
+    LAST_RECOVERY.lock().ok()?.clone()
+}
@@ -0,0 +1,321 @@
+//! 修改存储位置
+//!
+//! 目标位置已有存储时不直接覆盖，而是返回 StorageExists 错误，由用户选择改用目标位置的数据、
+//! 覆盖它，或将当前数据合并进去（按 id、平台 + 邮箱匹配账户，见 core::merge）。
+//! 可选将旧位置的备份、快照和审计日志一并移动到新位置。
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+
+use super::audit::{self, AuditAction};
+use super::error::{AppError, ErrorKind};
+use super::merge::{merge_storage, ImportReport, MergeStrategy};
+use super::recovery;
+use super::snapshot;
+use super::sqlite::SqliteStore;
+use super::settings;
+use super::storage::{
+    app_data_storage_path, get_storage_backend, get_storage_path, save_custom_path_to_config,
+    set_vault_status, Storage, StorageBackend, StorageConfig,
+};
+use super::vault::VaultStatus;
+use super::vault_registry;
+use crate::commands::AppState;
+use crate::utils::logger::{log_info, log_warn};
+
+/// 目标位置已有存储时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExistingStore {
+    /// 改用目标位置的数据，当前数据留在原位置
+    Adopt,
+    /// 用当前数据覆盖目标位置的数据
+    Overwrite,
+    /// 将当前数据合并到目标位置
+    Merge,
+}
+
+/// 修改存储位置的结果
+#[derive(Debug, Serialize)]
+pub struct StoragePathChange {
+    pub path: String,
+    /// 合并报告，仅 Merge 时存在
+    pub merge: Option<ImportReport>,
+    /// 随存储一起移动的文件数
+    pub moved_files: usize,
+}
+
+/// 当前后端在该位置的存储文件（JSON 后端为 accounts.json，SQLite 后端为 accounts.db）
+fn store_file(path: &Path) -> PathBuf {
+    match get_storage_backend() {
+        StorageBackend::Json => path.to_path_buf(),
+        StorageBackend::Sqlite => path.with_extension("db"),
+    }
+}
+
+fn read_store(app: &AppHandle, path: &Path) -> Result<Storage, String> {
+    match get_storage_backend() {
+        StorageBackend::Json => {
+            let content = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            Storage::decode_backup(app, &content)
+        }
+        StorageBackend::Sqlite => SqliteStore::open(&path.with_extension("db"))?.load(),
+    }
+}
+
+fn set_custom_path(config: &StorageConfig, path: Option<&PathBuf>) -> Result<(), String> {
+    save_custom_path_to_config(path)?;
+    *config.custom_path.lock().map_err(|e| e.to_string())? = path.cloned();
+    Ok(())
+}
+
+/// 切换到新路径后按处理方式写入或加载数据，返回合并报告
+fn apply(
+    app: &AppHandle,
+    storage: &mut Storage,
+    target: &Path,
+    action: Option<ExistingStore>,
+    strategy: MergeStrategy,
+) -> Result<Option<ImportReport>, String> {
+    match action {
+        Some(ExistingStore::Adopt) => {
+            // 目标位置可能使用其他主密码加密，需要重新解锁
+            set_vault_status(app, VaultStatus::Plain)?;
+            *storage = Storage::load_session(app);
+            Ok(None)
+        }
+        Some(ExistingStore::Merge) => {
+            let mut merged = read_store(app, target)?;
+            let before = merged.accounts.clone();
+            let report = merge_storage(&mut merged, storage, strategy);
+            merged.save_over(app)?;
+            audit::record(app, &audit::diff_accounts(AuditAction::Import, &before, &merged.accounts));
+            *storage = merged;
+            Ok(Some(report))
+        }
+        Some(ExistingStore::Overwrite) | None => {
+            storage.save_over(app)?;
+            Ok(None)
+        }
+    }
+}
+
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    // rename 不能跨文件系统，失败时改为复制后删除
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)
+        .and_then(|_| fs::remove_file(from))
+        .map_err(|e| format!("Failed to move {} to {}: {}", from.display(), to.display(), e))
+}
+
+/// 将旧位置的备份、快照和审计日志移动到新位置，返回移动的文件数。
+/// 旧存储文件改名为 .moved-<时间> 一并移动，不会直接删除。
+/// 不用 .bak 后缀：它是另一份存储的数据，不能被当作新位置的备份用于恢复
+fn move_storage_files(from: &Path, to: &Path) -> usize {
+    let from_name = from.file_name().and_then(|n| n.to_str()).unwrap_or("accounts.json");
+    let to_name = to.file_name().and_then(|n| n.to_str()).unwrap_or("accounts.json");
+    let same_dir = from.parent() == to.parent();
+
+    let (mut moved, mut appended) = (0, 0);
+    let mut move_one = |src: &Path, dst: PathBuf| {
+        if dst.exists() {
+            log_warn(format!("Not moving {}: {} already exists", src.display(), dst.display()));
+            return;
+        }
+        match move_file(src, &dst) {
+            Ok(()) => moved += 1,
+            Err(e) => log_warn(e),
+        }
+    };
+
+    for backup in recovery::find_backups(from) {
+        let Some(name) = backup.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        move_one(&backup, to.with_file_name(name.replacen(from_name, to_name, 1)));
+    }
+
+    // 同一目录下只是改了文件名时，快照和审计日志本来就是共用的
+    if !same_dir {
+        let snapshot_dir = snapshot::snapshot_dir(to);
+        let snapshots = snapshot::list_snapshot_paths(from);
+        if !snapshots.is_empty() {
+            if let Err(e) = fs::create_dir_all(&snapshot_dir) {
+                log_warn(format!("Failed to create {}: {}", snapshot_dir.display(), e));
+            }
+        }
+        for path in snapshots {
+            if let Some(name) = path.file_name() {
+                move_one(&path, snapshot_dir.join(name));
+            }
+        }
+
+        let (old_audit, new_audit) = (audit::audit_path(from), audit::audit_path(to));
+        let audit_files = [
+            (audit::rotated_path(&old_audit), audit::rotated_path(&new_audit)),
+            (old_audit, new_audit),
+        ];
+        for (old_audit, new_audit) in audit_files {
+            if !old_audit.exists() {
+                continue;
+            }
+            if new_audit.exists() {
+                match append_file(&old_audit, &new_audit) {
+                    Ok(()) => appended += 1,
+                    Err(e) => log_warn(e),
+                }
+            } else {
+                move_one(&old_audit, new_audit);
+            }
+        }
+    }
+
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    for old in [from.to_path_buf(), from.with_extension("db")] {
+        if !old.exists() {
+            continue;
+        }
+        let name = old.file_name().and_then(|n| n.to_str()).unwrap_or(from_name);
+        let name = name.replacen(from_name, to_name, 1);
+        move_one(&old, to.with_file_name(format!("{}.moved-{}", name, timestamp)));
+    }
+
+    moved + appended
+}
+
+/// 将 from 的内容追加到 to 后删除 from（合并审计日志）
+fn append_file(from: &Path, to: &Path) -> Result<(), String> {
+    let content = fs::read(from)
+        .map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(to)
+        .map_err(|e| format!("Failed to open {}: {}", to.display(), e))?;
+    file.write_all(&content)
+        .map_err(|e| format!("Failed to write {}: {}", to.display(), e))?;
+    fs::remove_file(from)
+        .map_err(|e| format!("Failed to remove {}: {}", from.display(), e))
+}
+
+/// 修改默认存储库的位置，path 为空时恢复为应用数据目录
+///
+/// 目标位置已有存储且未指定 existing 时返回 StorageExists 错误；
+/// merge 时按 strategy 合并账户（默认保留较新的一方）
+#[tauri::command]
+pub fn set_storage_path(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    config: State<'_, StorageConfig>,
+    path: String,
+    existing: Option<ExistingStore>,
+    strategy: Option<MergeStrategy>,
+    move_files: Option<bool>,
+) -> Result<StoragePathChange, AppError> {
+    if let Some(vault) = vault_registry::current_vault() {
+        return Err(format!("Vault '{}' is open, close it before changing the default storage path", vault.name).into());
+    }
+
+    let custom = (!path.is_empty()).then(|| PathBuf::from(&path));
+    let target = match &custom {
+        Some(path) => path.clone(),
+        None => app_data_storage_path(&app)?,
+    };
+    let previous = get_storage_path(&app)?;
+    let unchanged = StoragePathChange {
+        path: target.to_string_lossy().to_string(),
+        merge: None,
+        moved_files: 0,
+    };
+    if target == previous {
+        set_custom_path(&config, custom.as_ref())?;
+        return Ok(unchanged);
+    }
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    // 持有存储锁完成切换，期间其他命令不会写入任何一个位置
+    let mut storage = state.storage.lock().unwrap();
+    let action = match (store_file(&target), existing) {
+        (file, None) if file.exists() => {
+            return Err(AppError::new(
+                ErrorKind::StorageExists,
+                format!("{} already contains account data, choose adopt, overwrite or merge", file.display()),
+            ));
+        }
+        (file, action) if file.exists() => action,
+        _ => None,
+    };
+    if storage.locked && action != Some(ExistingStore::Adopt) {
+        return Err(AppError::locked());
+    }
+
+    let previous_custom = settings::load().storage_path;
+    set_custom_path(&config, custom.as_ref())?;
+
+    let merge = match apply(&app, &mut storage, &target, action, strategy.unwrap_or(MergeStrategy::KeepNewer)) {
+        Ok(report) => report,
+        Err(e) => {
+            set_custom_path(&config, previous_custom.as_ref())?;
+            return Err(e.into());
+        }
+    };
+    drop(storage);
+
+    let moved_files = if move_files.unwrap_or(false) {
+        move_storage_files(&previous, &target)
+    } else {
+        0
+    };
+
+    log_info(format!(
+        "Storage path changed from {} to {} ({:?}, {} files moved)",
+        previous.display(), target.display(), action, moved_files
+    ));
+    Ok(StoragePathChange { merge, moved_files, ..unchanged })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_storage_files() {
+        let root = crate::core::test_support::temp_dir("relocate");
+        let (old_dir, new_dir) = (root.join("old"), root.join("new"));
+        fs::create_dir_all(old_dir.join("snapshots")).unwrap();
+        fs::create_dir_all(&new_dir).unwrap();
+
+        let from = old_dir.join("accounts.json");
+        let to = new_dir.join("accounts.json");
+        fs::write(&from, "{}").unwrap();
+        fs::write(old_dir.join("accounts.json.bak"), "{}").unwrap();
+        fs::write(old_dir.join("audit.jsonl"), "old\n").unwrap();
+        fs::write(new_dir.join("audit.jsonl"), "new\n").unwrap();
+        fs::write(old_dir.join("snapshots").join("accounts-20260101-000000.json.gz"), "").unwrap();
+
+        // 备份、快照、审计日志（追加）和改名后的旧存储文件
+        let moved = move_storage_files(&from, &to);
+        assert_eq!(moved, 4);
+        assert!(new_dir.join("accounts.json.bak").exists());
+        assert_eq!(snapshot::list_snapshot_paths(&to).len(), 1);
+        assert!(!from.exists());
+        // 旧存储文件保留在新文件夹中，但不是恢复候选
+        let kept = fs::read_dir(&new_dir).unwrap()
+            .filter_map(|entry| entry.ok())
+            .any(|entry| entry.file_name().to_string_lossy().starts_with("accounts.json.moved-"));
+        assert!(kept);
+        assert!(!recovery::find_backups(&to).iter().any(|p| p.to_string_lossy().contains(".moved-")));
+        assert_eq!(fs::read_to_string(new_dir.join("audit.jsonl")).unwrap(), "new\nold\n");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,462 @@
+//! 密钥存储后端
+//!
+//! platform_data 中的 Token、客户端密钥、API Key 等字段（按字段名识别，见 utils::redact）
+//! 可以不与账户元数据一起写入存储文件，而是交给 `SecretStore` 保存，账户记录中只留下引用
+//! `nexus-secret://<后端>/<存储范围>/<账户 id>/<字段路径>`。存储范围由存储文件路径得出，
+//! 不同存储库（或导入、合并得到的副本）中 id 相同的账户不会互相覆盖。
+//! 内存中的 Storage 始终持有真实值：写入磁盘时通过 `with_refs` 换成引用，读取时通过 `resolve` 换回。
+//! 后端暂时不可用（如密钥环未解锁）时引用保持原样，存储进入只读状态，下次访问时重试。
+//!
+//! 后端由设置项 `secret_backend` 选择，切换时需通过 migrate_secrets 迁移已有账户。
+//! 引用中记录了后端，设置与文件不一致时仍能读取。永久删除账户时不删除后端中的条目，
+//! 以便快照恢复后仍能找回密钥。
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, State};
+
+use super::platform::PlatformData;
+use super::settings;
+use super::storage::{get_storage_path, Account, Storage};
+use crate::commands::AppState;
+use crate::core::error::AppError;
+use crate::utils::logger::{log_error, log_info, log_warn};
+use crate::utils::redact::is_secret_key;
+
+/// 引用前缀
+const SECRET_REF_PREFIX: &str = "nexus-secret://";
+
+/// 系统密钥环中的服务名
+#[cfg(target_os = "linux")]
+const KEYRING_SERVICE: &str = "com.nexus.account-manager";
+
+/// 密钥后端
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretBackend {
+    /// 与账户数据一起保存在存储文件中（默认）
+    #[default]
+    Inline,
+    /// 系统密钥环（Linux Secret Service）
+    Keyring,
+    /// 进程内存，退出后丢失，仅用于测试
+    Memory,
+}
+
+impl SecretBackend {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Inline => "inline",
+            Self::Keyring => "keyring",
+            Self::Memory => "memory",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "inline" => Some(Self::Inline),
+            "keyring" => Some(Self::Keyring),
+            "memory" => Some(Self::Memory),
+            _ => None,
+        }
+    }
+}
+
+/// 密钥存储
+pub trait SecretStore: Send + Sync {
+    fn backend(&self) -> SecretBackend;
+    /// 读取密钥，不存在时返回 None
+    fn get(&self, key: &str) -> Result<Option<String>, String>;
+    fn set(&self, key: &str, value: &str) -> Result<(), String>;
+    fn delete(&self, key: &str) -> Result<(), String>;
+}
+
+/// 内联：密钥原样留在账户记录中，后端本身不保存任何条目
+pub struct InlineStore;
+
+impl SecretStore for InlineStore {
+    fn backend(&self) -> SecretBackend {
+        SecretBackend::Inline
+    }
+
+    fn get(&self, _key: &str) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    fn set(&self, _key: &str, _value: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn delete(&self, _key: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// 内存
+#[derive(Default)]
+pub struct MemoryStore {
+    entries: Mutex<HashMap<String, String>>,
+}
+
+impl SecretStore for MemoryStore {
+    fn backend(&self) -> SecretBackend {
+        SecretBackend::Memory
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        Ok(self.entries.lock().map_err(|e| e.to_string())?.get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), String> {
+        self.entries.lock().map_err(|e| e.to_string())?.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        self.entries.lock().map_err(|e| e.to_string())?.remove(key);
+        Ok(())
+    }
+}
+
+/// 系统密钥环（Linux Secret Service，如 GNOME Keyring、KWallet）
+///
+/// 每次保存都会写入所有密钥，用缓存跳过未变化的条目，避免大量 D-Bus 调用
+#[cfg(target_os = "linux")]
+#[derive(Default)]
+pub struct KeyringStore {
+    cache: Mutex<HashMap<String, String>>,
+}
+
+#[cfg(target_os = "linux")]
+impl KeyringStore {
+    fn entry(key: &str) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(KEYRING_SERVICE, key)
+            .map_err(|e| format!("Failed to open keyring entry {}: {}", key, e))
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl SecretStore for KeyringStore {
+    fn backend(&self) -> SecretBackend {
+        SecretBackend::Keyring
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        if let Some(value) = self.cache.lock().map_err(|e| e.to_string())?.get(key) {
+            return Ok(Some(value.clone()));
+        }
+        match Self::entry(key)?.get_password() {
+            Ok(value) => {
+                self.cache.lock().map_err(|e| e.to_string())?.insert(key.to_string(), value.clone());
+                Ok(Some(value))
+            }
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("Failed to read {} from keyring: {}", key, e)),
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), String> {
+        let mut cache = self.cache.lock().map_err(|e| e.to_string())?;
+        if cache.get(key).is_some_and(|cached| cached == value) {
+            return Ok(());
+        }
+        Self::entry(key)?
+            .set_password(value)
+            .map_err(|e| format!("Failed to write {} to keyring: {}", key, e))?;
+        cache.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        self.cache.lock().map_err(|e| e.to_string())?.remove(key);
+        match Self::entry(key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to delete {} from keyring: {}", key, e)),
+        }
+    }
+}
+
+static MEMORY: Lazy<Arc<MemoryStore>> = Lazy::new(Default::default);
+#[cfg(target_os = "linux")]
+static KEYRING: Lazy<Arc<KeyringStore>> = Lazy::new(Default::default);
+
+/// 获取指定后端
+pub fn open(backend: SecretBackend) -> Result<Arc<dyn SecretStore>, String> {
+    match backend {
+        SecretBackend::Inline => Ok(Arc::new(InlineStore)),
+        SecretBackend::Memory => Ok(MEMORY.clone()),
+        #[cfg(target_os = "linux")]
+        SecretBackend::Keyring => Ok(KEYRING.clone()),
+        #[cfg(not(target_os = "linux"))]
+        SecretBackend::Keyring => Err("Keyring secret backend is only supported on Linux".to_string()),
+    }
+}
+
+/// 设置中选择的后端
+pub fn active() -> Result<Arc<dyn SecretStore>, String> {
+    open(settings::load().secret_backend)
+}
+
+/// 存储文件对应的范围：路径的 SHA-1 前 16 位
+fn scope_for(path: &Path) -> String {
+    let digest = Sha1::digest(path.to_string_lossy().as_bytes());
+    digest.iter().take(8).map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 当前存储库的范围
+fn scope(app: &AppHandle) -> Result<String, String> {
+    Ok(scope_for(&get_storage_path(app)?))
+}
+
+fn reference(backend: SecretBackend, key: &str) -> String {
+    format!("{}{}/{}", SECRET_REF_PREFIX, backend.as_str(), key)
+}
+
+fn parse_reference(value: &str) -> Option<(SecretBackend, &str)> {
+    let (backend, key) = value.strip_prefix(SECRET_REF_PREFIX)?.split_once('/')?;
+    Some((SecretBackend::from_str(backend)?, key))
+}
+
+/// 遍历 JSON 中的密钥字段：字段名为敏感字段的字符串，数组元素沿用数组的字段名
+fn for_each_secret(
+    value: &mut Value,
+    path: &str,
+    key: Option<&str>,
+    f: &mut dyn FnMut(&str, &mut String) -> Result<(), String>,
+) -> Result<(), String> {
+    match value {
+        Value::String(s) if key.is_some_and(is_secret_key) => f(path, s),
+        Value::Object(map) => {
+            for (child_key, child) in map.iter_mut() {
+                for_each_secret(child, &format!("{}/{}", path, child_key), Some(child_key), f)?;
+            }
+            Ok(())
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter_mut().enumerate() {
+                for_each_secret(child, &format!("{}/{}", path, i), key, f)?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 将账户的密钥写入 store 并替换为引用，返回写入的数量；已经是引用的值保持不变
+fn store_account(account: &mut Account, store: &dyn SecretStore, scope: &str) -> Result<usize, String> {
+    if store.backend() == SecretBackend::Inline {
+        return Ok(0);
+    }
+    let mut data = account.platform_data.to_value();
+    let mut count = 0;
+    for_each_secret(&mut data, &format!("{}/{}", scope, account.id), None, &mut |path, secret| {
+        if parse_reference(secret).is_none() {
+            store.set(path, secret)?;
+            *secret = reference(store.backend(), path);
+            count += 1;
+        }
+        Ok(())
+    })?;
+    account.platform_data = PlatformData::parse_lenient(&account.platform, data);
+    Ok(count)
+}
+
+/// 将账户中的引用换回真实值，返回换回的数量。条目不存在时保留引用并记录警告
+fn resolve_account(account: &mut Account, open: &dyn Fn(SecretBackend) -> Result<Arc<dyn SecretStore>, String>) -> Result<usize, String> {
+    let mut data = account.platform_data.to_value();
+    let mut count = 0;
+    for_each_secret(&mut data, &account.id, None, &mut |_, secret| {
+        let Some((backend, key)) = parse_reference(secret) else {
+            return Ok(());
+        };
+        match open(backend)?.get(key)? {
+            Some(value) => {
+                *secret = value;
+                count += 1;
+            }
+            None => log_warn(format!("Secret {} not found in {} backend", key, backend.as_str())),
+        }
+        Ok(())
+    })?;
+    if count > 0 {
+        account.platform_data = PlatformData::parse_lenient(&account.platform, data);
+    }
+    Ok(count)
+}
+
+fn accounts_mut(storage: &mut Storage) -> impl Iterator<Item = &mut Account> {
+    storage.accounts.iter_mut().chain(storage.trash.iter_mut().map(|d| &mut d.account))
+}
+
+/// 写入磁盘用的副本：密钥保存到当前后端，副本中只留引用
+pub fn with_refs(app: &AppHandle, storage: &Storage) -> Result<Storage, String> {
+    let store = active()?;
+    let mut disk = storage.clone();
+    if store.backend() != SecretBackend::Inline {
+        let scope = scope(app)?;
+        for account in accounts_mut(&mut disk) {
+            store_account(account, store.as_ref(), &scope)?;
+        }
+    }
+    Ok(disk)
+}
+
+/// 单个账户写入磁盘用的副本（SQLite 后端单行写入）
+pub fn account_with_refs(app: &AppHandle, account: &Account) -> Result<Account, String> {
+    let mut disk = account.clone();
+    store_account(&mut disk, active()?.as_ref(), &scope(app)?)?;
+    Ok(disk)
+}
+
+/// 从磁盘读取后换回真实值。后端出错时保留其余引用并记入 secrets_error（存储只读），
+/// 不作为文件损坏处理
+pub fn resolve(storage: &mut Storage) {
+    let error = accounts_mut(storage).find_map(|account| resolve_account(account, &open).err());
+    if let Some(e) = &error {
+        log_error(format!("Secret backend unavailable, storage is read-only until it can be read: {}", e));
+    }
+    storage.secrets_error = error;
+}
+
+/// 迁移结果
+#[derive(Debug, Serialize)]
+pub struct SecretMigrationReport {
+    pub from: SecretBackend,
+    pub to: SecretBackend,
+    pub accounts: usize,
+    /// 写入新后端的密钥数量
+    pub secrets: usize,
+    /// 从旧后端删除的条目数量
+    pub removed: usize,
+}
+
+/// 将所有账户的密钥迁移到新后端并切换设置；remove_old 时删除旧后端中的条目
+/// （旧快照中的引用将无法再解析）
+#[tauri::command]
+pub fn migrate_secrets(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    backend: SecretBackend,
+    remove_old: Option<bool>,
+) -> Result<SecretMigrationReport, AppError> {
+    if backend == SecretBackend::Memory {
+        return Err("Memory secret backend is only meant for tests".into());
+    }
+    let mut storage = state.storage()?;
+    let from = settings::load().secret_backend;
+    let (old, new) = (open(from)?, open(backend)?);
+    let scope = scope(&app)?;
+
+    // 预先写入新后端，统计数量并尽早发现后端不可用
+    let mut secrets = 0;
+    for account in storage.accounts.iter().chain(storage.trash.iter().map(|d| &d.account)) {
+        secrets += store_account(&mut account.clone(), new.as_ref(), &scope)?;
+    }
+
+    settings::update(|s| s.secret_backend = backend)?;
+    if let Err(e) = storage.save(&app) {
+        settings::update(|s| s.secret_backend = from)?;
+        return Err(e.into());
+    }
+
+    let mut removed = 0;
+    if remove_old.unwrap_or(false) && from != backend && from != SecretBackend::Inline {
+        for account in storage.accounts.iter().chain(storage.trash.iter().map(|d| &d.account)) {
+            let mut data = account.platform_data.to_value();
+            for_each_secret(&mut data, &format!("{}/{}", scope, account.id), None, &mut |path, _| {
+                match old.delete(path) {
+                    Ok(()) => removed += 1,
+                    Err(e) => log_warn(e),
+                }
+                Ok(())
+            })?;
+        }
+    }
+
+    let report = SecretMigrationReport {
+        from,
+        to: backend,
+        accounts: storage.accounts.len() + storage.trash.len(),
+        secrets,
+        removed,
+    };
+    log_info(format!(
+        "Migrated {} secrets of {} accounts from {:?} to {:?} backend",
+        report.secrets, report.accounts, from, backend
+    ));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::account_with_data;
+    use serde_json::json;
+
+    fn claude_account() -> Account {
+        account_with_data("acc1", "claude", "", json!({
+            "config": { "env": { "ANTHROPIC_API_KEY": "sk-ant-123", "ANTHROPIC_BASE_URL": "https://api" } }
+        }))
+    }
+
+    #[test]
+    fn test_store_and_resolve() {
+        let store = Arc::new(MemoryStore::default());
+        let mut account = claude_account();
+
+        assert_eq!(store_account(&mut account, store.as_ref(), "s1").unwrap(), 1);
+        let data = account.platform_data.to_value();
+        assert_eq!(data["config"]["env"]["ANTHROPIC_API_KEY"], "nexus-secret://memory/s1/acc1/config/env/ANTHROPIC_API_KEY");
+        assert_eq!(data["config"]["env"]["ANTHROPIC_BASE_URL"], "https://api");
+        assert_eq!(store.get("s1/acc1/config/env/ANTHROPIC_API_KEY").unwrap().as_deref(), Some("sk-ant-123"));
+        // 已经是引用的值不会重复写入
+        assert_eq!(store_account(&mut account, store.as_ref(), "s1").unwrap(), 0);
+
+        // 另一个存储库中 id 相同的账户写入不同的条目
+        let mut copy = Account {
+            platform_data: PlatformData::parse("claude", json!({ "config": { "env": { "ANTHROPIC_API_KEY": "sk-ant-456" } } })).unwrap(),
+            ..claude_account()
+        };
+        store_account(&mut copy, store.as_ref(), "s2").unwrap();
+        assert_eq!(store.get("s1/acc1/config/env/ANTHROPIC_API_KEY").unwrap().as_deref(), Some("sk-ant-123"));
+        assert_ne!(scope_for(Path::new("/a/accounts.json")), scope_for(Path::new("/b/accounts.json")));
+
+        let opener = {
+            let store = store.clone();
+            move |_: SecretBackend| -> Result<Arc<dyn SecretStore>, String> { Ok(store.clone()) }
+        };
+        assert_eq!(resolve_account(&mut account, &opener).unwrap(), 1);
+        assert_eq!(account.platform_data, claude_account().platform_data);
+    }
+
+    #[test]
+    fn test_inline_and_missing() {
+        let mut account = claude_account();
+        assert_eq!(store_account(&mut account, &InlineStore, "s1").unwrap(), 0);
+        assert_eq!(account.platform_data, claude_account().platform_data);
+
+        // 条目不存在时保留引用
+        let empty = Arc::new(MemoryStore::default());
+        let store = MemoryStore::default();
+        store_account(&mut account, &store, "s1").unwrap();
+        let opener = move |_: SecretBackend| -> Result<Arc<dyn SecretStore>, String> { Ok(empty.clone()) };
+        assert_eq!(resolve_account(&mut account, &opener).unwrap(), 0);
+        assert!(parse_reference(account.platform_data.to_value()["config"]["env"]["ANTHROPIC_API_KEY"].as_str().unwrap()).is_some());
+
+        // 后端不可用时返回错误，由 resolve 标记为只读而不是视为文件损坏
+        let unavailable = |_: SecretBackend| -> Result<Arc<dyn SecretStore>, String> { Err("keyring is locked".to_string()) };
+        assert!(resolve_account(&mut account, &unavailable).is_err());
+    }
+
+    #[test]
+    fn test_parse_reference() {
+        assert_eq!(parse_reference(&reference(SecretBackend::Keyring, "a/b")), Some((SecretBackend::Keyring, "a/b")));
+        assert_eq!(parse_reference("nexus-secret://unknown/a"), None);
+        assert_eq!(parse_reference("sk-ant-123"), None);
+    }
+}
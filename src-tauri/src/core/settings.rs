@@ -0,0 +1,422 @@
+//! 应用设置
+//!
+//! 所有设置保存在应用数据目录下的 config.json，由 `AppSettings` 统一描述：缺省字段取默认值，
+//! 写入前校验，`version` 记录结构版本以便迁移。未识别的字段原样保留（可能由更新的版本写入）。
+//! 修改设置后发出 `settings-changed` 事件，携带修改后的完整设置。
+//! 读取频繁（每次取存储路径、后端等都会用到），解析结果缓存在内存中，只在修改设置时更新。
+//!
+//! 旧版本的 Antigravity 路径保存在另一个 config.json（开发模式下在项目目录，发布模式下在
+//! `{data_dir}/nexus-account-manager`），首次加载时迁移到这里。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::secret_store::SecretBackend;
+use super::snapshot::SnapshotSettings;
+use super::storage::{StorageBackend, DEFAULT_TRASH_RETENTION_DAYS};
+use super::vault::{VaultState, DEFAULT_AUTO_LOCK_MINUTES};
+use super::vault_registry::VaultEntry;
+use crate::commands::AppState;
+use crate::core::error::AppError;
+use crate::utils::atomic_file::write_atomic;
+use crate::utils::logger::{self, log_info, log_warn, LogRotation, MAX_LOG_FILES};
+
+/// 当前设置结构版本
+pub const SETTINGS_SCHEMA_VERSION: u32 = 1;
+
+/// 设置修改事件
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// 空闲自动锁定时间上限（分钟）
+const MAX_AUTO_LOCK_MINUTES: u64 = 24 * 60;
+/// 回收站保留天数上限
+const MAX_TRASH_RETENTION_DAYS: u64 = 3650;
+
+/// 只能通过专用命令修改的字段：修改它们需要同时迁移数据或切换存储
+const PROTECTED_KEYS: &[&str] = &["version", "storage_path", "storage_backend", "secret_backend", "vaults", "current_vault"];
+
+/// 应用设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub version: u32,
+    /// 默认存储库的 accounts.json 路径，None 表示应用数据目录
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_path: Option<PathBuf>,
+    pub storage_backend: StorageBackend,
+    /// 密钥后端，见 core::secret_store
+    pub secret_backend: SecretBackend,
+    /// 空闲自动锁定时间（分钟），0 表示关闭
+    pub auto_lock_minutes: u64,
+    /// 回收站保留天数，0 表示不自动清除
+    pub trash_retention_days: u64,
+    pub snapshots: SnapshotSettings,
+    /// 日志轮转，见 utils::logger
+    pub log_rotation: LogRotation,
+    /// 已登记的命名存储库
+    pub vaults: Vec<VaultEntry>,
+    /// 当前打开的命名存储库，None 表示默认存储库
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_vault: Option<String>,
+    /// Antigravity 可执行文件路径（自动检测或用户手动配置）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub antigravity_executable: Option<String>,
+    /// Antigravity 启动参数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub antigravity_args: Option<Vec<String>>,
+    /// 未识别的字段
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_SCHEMA_VERSION,
+            storage_path: None,
+            storage_backend: StorageBackend::default(),
+            secret_backend: SecretBackend::default(),
+            auto_lock_minutes: DEFAULT_AUTO_LOCK_MINUTES,
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            snapshots: SnapshotSettings::default(),
+            log_rotation: LogRotation::default(),
+            vaults: Vec::new(),
+            current_vault: None,
+            antigravity_executable: None,
+            antigravity_args: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// 写入前校验
+    pub fn validate(&self) -> Result<(), String> {
+        if self.auto_lock_minutes > MAX_AUTO_LOCK_MINUTES {
+            return Err(format!("auto_lock_minutes must be at most {}", MAX_AUTO_LOCK_MINUTES));
+        }
+        if self.trash_retention_days > MAX_TRASH_RETENTION_DAYS {
+            return Err(format!("trash_retention_days must be at most {}", MAX_TRASH_RETENTION_DAYS));
+        }
+        if self.log_rotation.max_files > MAX_LOG_FILES {
+            return Err(format!("log_rotation.max_files must be at most {}", MAX_LOG_FILES));
+        }
+        if self.storage_path.as_ref().is_some_and(|p| p.as_os_str().is_empty()) {
+            return Err("storage_path cannot be empty".to_string());
+        }
+        if self.antigravity_executable.as_ref().is_some_and(|p| p.trim().is_empty()) {
+            return Err("antigravity_executable cannot be empty".to_string());
+        }
+        for (i, vault) in self.vaults.iter().enumerate() {
+            if vault.name.is_empty() || self.vaults[..i].iter().any(|v| v.name == vault.name) {
+                return Err(format!("Invalid or duplicate vault name '{}'", vault.name));
+            }
+        }
+        if let Some(name) = &self.current_vault {
+            if !self.vaults.iter().any(|v| &v.name == name) {
+                return Err(format!("current_vault '{}' is not a registered vault", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+struct SettingsService {
+    app: AppHandle,
+    path: PathBuf,
+    /// 串行化读-改-写
+    write: Mutex<()>,
+    /// 最近一次读取或写入的设置，None 表示需要重新读取
+    cache: Mutex<Option<AppSettings>>,
+}
+
+static SERVICE: OnceLock<SettingsService> = OnceLock::new();
+
+/// 初始化设置服务并完成旧版本迁移，需在读取任何设置之前调用
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("config.json");
+    let service = SERVICE.get_or_init(|| SettingsService {
+        app: app.clone(),
+        path,
+        write: Mutex::new(()),
+        cache: Mutex::new(None),
+    });
+
+    let _guard = service.write.lock().map_err(|e| e.to_string())?;
+    // 无法解析的配置文件改名保留，以默认设置重新开始
+    let current = match read_value(service).and_then(|value| parse(value.clone()).map(|_| value)) {
+        Ok(value) => value,
+        Err(e) => {
+            let backup = service.path.with_extension(format!("json.invalid-{}", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+            log_warn(format!("{}, moving it to {}", e, backup.display()));
+            let _ = fs::rename(&service.path, &backup);
+            Value::Object(Map::new())
+        }
+    };
+    let legacy = legacy_config_path()
+        .filter(|p| *p != service.path)
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|content| serde_json::from_str(&content).ok());
+    let (settings, migrated) = migrate(current, legacy);
+    if migrated {
+        write(service, &settings)?;
+        log_info(format!("Settings migrated to schema version {}", settings.version));
+    }
+    *service.cache.lock().map_err(|e| e.to_string())? = Some(settings);
+    Ok(())
+}
+
+/// 读取当前设置，未初始化或文件无效时返回默认设置
+pub fn load() -> AppSettings {
+    let Some(service) = SERVICE.get() else {
+        return AppSettings::default();
+    };
+    let mut cache = service.cache.lock().unwrap();
+    if let Some(settings) = cache.as_ref() {
+        return settings.clone();
+    }
+    // 读取失败时不缓存，文件修复后即可重新读到
+    match read_value(service).and_then(parse) {
+        Ok(settings) => {
+            *cache = Some(settings.clone());
+            settings
+        }
+        Err(e) => {
+            log_warn(format!("Failed to load settings, using defaults: {}", e));
+            AppSettings::default()
+        }
+    }
+}
+
+/// 修改设置：校验通过后写入并发出 settings-changed 事件，返回修改后的设置
+pub fn update(f: impl FnOnce(&mut AppSettings)) -> Result<AppSettings, String> {
+    try_update(|settings| {
+        f(settings);
+        Ok(())
+    })
+}
+
+/// 同 update，f 返回错误时不写入
+fn try_update(f: impl FnOnce(&mut AppSettings) -> Result<(), String>) -> Result<AppSettings, String> {
+    let service = SERVICE.get().ok_or("Settings not initialized")?;
+    let _guard = service.write.lock().map_err(|e| e.to_string())?;
+
+    let mut settings = parse(read_value(service)?)?;
+    f(&mut settings)?;
+    settings.validate()?;
+    let mut cache = service.cache.lock().map_err(|e| e.to_string())?;
+    // 写入失败时文件内容未知，让下次读取重新解析
+    *cache = None;
+    write(service, &settings)?;
+    *cache = Some(settings.clone());
+    drop(cache);
+
+    let _ = service.app.emit(SETTINGS_CHANGED_EVENT, &settings);
+    Ok(settings)
+}
+
+fn read_value(service: &SettingsService) -> Result<Value, String> {
+    if !service.path.exists() {
+        return Ok(Value::Object(Map::new()));
+    }
+    let content = fs::read_to_string(&service.path)
+        .map_err(|e| format!("Failed to read config: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse config: {}", e))
+}
+
+fn write(service: &SettingsService, settings: &AppSettings) -> Result<(), String> {
+    if let Some(parent) = service.path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    write_atomic(&service.path, content.as_bytes())
+}
+
+/// 解析配置文件内容；没有 version 的旧文件视为版本 0
+fn parse(value: Value) -> Result<AppSettings, String> {
+    let has_version = value.get("version").is_some();
+    let mut settings: AppSettings = serde_json::from_value(value)
+        .map_err(|e| format!("Invalid settings: {}", e))?;
+    if !has_version {
+        settings.version = 0;
+    }
+    Ok(settings)
+}
+
+/// 迁移到当前结构版本，返回迁移后的设置及是否需要写回
+fn migrate(value: Value, legacy: Option<Value>) -> (AppSettings, bool) {
+    let mut settings = parse(value).unwrap_or_else(|e| {
+        log_warn(format!("Failed to parse settings, resetting to defaults: {}", e));
+        AppSettings { version: 0, ..AppSettings::default() }
+    });
+    if settings.version >= SETTINGS_SCHEMA_VERSION {
+        return (settings, false);
+    }
+
+    // 版本 0 -> 1：合并旧版 utils::config 保存的 Antigravity 设置
+    if let Some(legacy) = legacy {
+        if settings.antigravity_executable.is_none() {
+            settings.antigravity_executable = legacy.get("antigravity_executable")
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+        }
+        if settings.antigravity_args.is_none() {
+            settings.antigravity_args = legacy.get("antigravity_args")
+                .and_then(|v| serde_json::from_value(v.clone()).ok());
+        }
+    }
+
+    settings.version = SETTINGS_SCHEMA_VERSION;
+    (settings, true)
+}
+
+/// 旧版 Antigravity 设置文件的位置
+fn legacy_config_path() -> Option<PathBuf> {
+    if cfg!(debug_assertions) {
+        let current = std::env::current_dir().ok()?;
+        let root = if current.ends_with("src-tauri") {
+            current.parent()?.to_path_buf()
+        } else {
+            current
+        };
+        Some(root.join("config").join("config.json"))
+    } else {
+        Some(dirs::data_dir()?.join(env!("CARGO_PKG_NAME")).join("config.json"))
+    }
+}
+
+/// 按 JSON Merge Patch（RFC 7386）合并：null 删除字段（即恢复默认值），对象逐层合并
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target) = target else {
+        return;
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// 将 patch 应用到设置上，拒绝修改只能通过专用命令修改的字段
+fn apply_patch(settings: &AppSettings, patch: &Value) -> Result<AppSettings, String> {
+    if !patch.is_object() {
+        return Err("Settings patch must be an object".to_string());
+    }
+    let mut value = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+    let before = value.clone();
+    merge_patch(&mut value, patch);
+
+    for key in PROTECTED_KEYS {
+        if value.get(key) != before.get(key) {
+            return Err(format!("'{}' cannot be changed through update_settings", key));
+        }
+    }
+    serde_json::from_value(value).map_err(|e| format!("Invalid settings: {}", e))
+}
+
+#[tauri::command]
+pub fn get_settings() -> AppSettings {
+    load()
+}
+
+/// 按 JSON Merge Patch 修改设置，并立即应用自动锁定时间、回收站保留天数和日志轮转设置
+/// （Vault 锁定时回收站在解锁后按新的保留天数清理）
+///
+/// 存储路径、存储后端、密钥后端和命名存储库需要通过 set_storage_path、set_storage_backend、
+/// migrate_secrets 和 open_vault 等命令修改
+#[tauri::command]
+pub fn update_settings(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    vault: State<'_, VaultState>,
+    patch: Value,
+) -> Result<AppSettings, AppError> {
+    let mut previous_retention = None;
+    let settings = try_update(|settings| {
+        previous_retention = Some(settings.trash_retention_days);
+        *settings = apply_patch(settings, &patch)?;
+        Ok(())
+    })?;
+
+    *vault.auto_lock_minutes.lock().map_err(|e| e.to_string())? = settings.auto_lock_minutes;
+    logger::set_log_rotation(settings.log_rotation.clone());
+    // 设置已经保存，清理失败只写日志
+    if previous_retention != Some(settings.trash_retention_days) {
+        if let Err(e) = state.storage.lock().unwrap().auto_purge_trash(&app) {
+            log_warn(format!("Failed to purge trash after changing retention: {}", e));
+        }
+    }
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_defaults_and_validation() {
+        let settings: AppSettings = serde_json::from_value(json!({ "auto_lock_minutes": 5 })).unwrap();
+        assert_eq!(settings.auto_lock_minutes, 5);
+        assert_eq!(settings.trash_retention_days, DEFAULT_TRASH_RETENTION_DAYS);
+        assert!(settings.validate().is_ok());
+
+        let invalid = AppSettings { auto_lock_minutes: MAX_AUTO_LOCK_MINUTES + 1, ..AppSettings::default() };
+        assert!(invalid.validate().is_err());
+        let invalid = AppSettings { current_vault: Some("work".to_string()), ..AppSettings::default() };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_migrate_legacy() {
+        let legacy = json!({ "antigravity_executable": "/opt/antigravity", "antigravity_args": ["--x"] });
+        let (settings, migrated) = migrate(json!({ "storage_path": "/data/accounts.json", "custom": 1 }), Some(legacy));
+        assert!(migrated);
+        assert_eq!(settings.version, SETTINGS_SCHEMA_VERSION);
+        assert_eq!(settings.antigravity_executable.as_deref(), Some("/opt/antigravity"));
+        assert_eq!(settings.antigravity_args, Some(vec!["--x".to_string()]));
+        assert_eq!(settings.storage_path, Some(PathBuf::from("/data/accounts.json")));
+        // 未识别的字段原样保留
+        assert_eq!(serde_json::to_value(&settings).unwrap()["custom"], json!(1));
+
+        let current = serde_json::to_value(&settings).unwrap();
+        let (_, migrated) = migrate(current, None);
+        assert!(!migrated);
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let settings = AppSettings::default();
+        let patched = apply_patch(&settings, &json!({ "auto_lock_minutes": 0, "snapshots": { "max_count": 5 } })).unwrap();
+        assert_eq!(patched.auto_lock_minutes, 0);
+        assert_eq!(patched.snapshots.max_count, 5);
+        assert_eq!(patched.snapshots.every_n_saves, settings.snapshots.every_n_saves);
+
+        // null 恢复默认值
+        let reset = apply_patch(&patched, &json!({ "auto_lock_minutes": null })).unwrap();
+        assert_eq!(reset.auto_lock_minutes, DEFAULT_AUTO_LOCK_MINUTES);
+
+        assert!(apply_patch(&settings, &json!({ "storage_backend": "sqlite" })).is_err());
+        assert!(apply_patch(&settings, &json!({ "auto_lock_minutes": "soon" })).is_err());
+        assert!(apply_patch(&settings, &json!([])).is_err());
+    }
+}
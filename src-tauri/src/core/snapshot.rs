@@ -0,0 +1,375 @@
+//! 账户数据快照
+//!
+//! 每次（或每 N 次）保存后，把写入磁盘的内容压缩为 `snapshots/accounts-<时间>.json.gz`，
+//! 按数量和天数清理旧快照。已启用加密时快照保存的是密文，读取前需要先解锁。
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, State};
+
+use super::audit::{self, AuditAction};
+use super::settings;
+use super::storage::{get_storage_path, Account, AccountSummary, Storage};
+use crate::commands::AppState;
+use crate::core::error::AppError;
+use crate::utils::atomic_file::write_atomic;
+use crate::utils::logger::{log_info, log_warn};
+
+const SNAPSHOT_DIR: &str = "snapshots";
+const SNAPSHOT_PREFIX: &str = "accounts-";
+const SNAPSHOT_SUFFIX: &str = ".json.gz";
+
+/// 自启动以来的保存次数，用于“每 N 次保存写一次快照”
+static SAVE_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// 快照设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotSettings {
+    /// 每 N 次保存写一次快照，0 表示关闭快照
+    pub every_n_saves: u64,
+    /// 最多保留的快照数量，0 表示不限
+    pub max_count: usize,
+    /// 快照最长保留天数，0 表示不限
+    pub max_age_days: u64,
+}
+
+impl Default for SnapshotSettings {
+    fn default() -> Self {
+        Self {
+            every_n_saves: 1,
+            max_count: 50,
+            max_age_days: 30,
+        }
+    }
+}
+
+impl SnapshotSettings {
+    pub fn load() -> Self {
+        settings::load().snapshots
+    }
+}
+
+/// 快照信息（返回给前端）
+#[derive(Debug, Serialize)]
+pub struct SnapshotInfo {
+    /// 快照文件名，作为 diff/restore 的参数
+    pub id: String,
+    pub created_at: i64,
+    pub size: u64,
+    pub encrypted: bool,
+    /// 快照中的账户数量，加密且未解锁时为 None
+    pub account_count: Option<usize>,
+}
+
+/// 有变化的账户及变化的字段名
+#[derive(Debug, Serialize)]
+pub struct AccountChange {
+    pub account: AccountSummary,
+    pub fields: Vec<String>,
+}
+
+/// 快照与当前数据的差异
+///
+/// 以快照为基准：`added` 是快照之后新增的账户，`removed` 是快照之后被删除的账户。
+/// 恢复快照会删除 `added`、找回 `removed`、还原 `changed`。
+#[derive(Debug, Default, Serialize)]
+pub struct SnapshotDiff {
+    pub added: Vec<AccountSummary>,
+    pub removed: Vec<AccountSummary>,
+    pub changed: Vec<AccountChange>,
+}
+
+/// 快照目录：存储文件同级的 snapshots/
+pub(crate) fn snapshot_dir(storage_path: &Path) -> PathBuf {
+    storage_path
+        .parent()
+        .map(|dir| dir.join(SNAPSHOT_DIR))
+        .unwrap_or_else(|| PathBuf::from(SNAPSHOT_DIR))
+}
+
+/// 列出全部快照文件，按时间从新到旧排序（文件名中的时间戳可直接按字典序比较）
+pub fn list_snapshot_paths(storage_path: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(snapshot_dir(storage_path)) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(SNAPSHOT_PREFIX) && n.ends_with(SNAPSHOT_SUFFIX))
+        })
+        .collect();
+
+    paths.sort();
+    paths.reverse();
+    paths
+}
+
+/// 读取备份或快照文件，`.gz` 文件自动解压
+pub fn read_backup_file(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    if path.extension().and_then(|e| e.to_str()) != Some("gz") {
+        return String::from_utf8(bytes).map_err(|e| e.to_string());
+    }
+
+    let mut content = String::new();
+    GzDecoder::new(bytes.as_slice())
+        .read_to_string(&mut content)
+        .map_err(|e| format!("Failed to decompress {}: {}", path.display(), e))?;
+    Ok(content)
+}
+
+/// 保存后调用：按设置决定是否写快照，失败只记录警告。
+/// content 只在需要写快照时调用，单行写入不必每次都序列化全部数据
+pub fn after_save(app: &AppHandle, content: impl FnOnce() -> Result<String, String>) {
+    let settings = SnapshotSettings::load();
+    if settings.every_n_saves == 0 {
+        return;
+    }
+
+    let count = SAVE_COUNT.fetch_add(1, Ordering::Relaxed) + 1;
+    if !count.is_multiple_of(settings.every_n_saves) {
+        return;
+    }
+
+    if let Err(e) = content().and_then(|content| write_snapshot(app, &content, &settings)) {
+        log_warn(format!("Failed to write snapshot: {}", e));
+    }
+}
+
+/// 写入快照并清理过期快照
+pub fn write_snapshot(app: &AppHandle, content: &str, settings: &SnapshotSettings) -> Result<PathBuf, String> {
+    let storage_path = get_storage_path(app)?;
+    let dir = snapshot_dir(&storage_path);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create snapshot directory: {}", e))?;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(content.as_bytes())
+        .map_err(|e| format!("Failed to compress snapshot: {}", e))?;
+    let compressed = encoder.finish()
+        .map_err(|e| format!("Failed to compress snapshot: {}", e))?;
+
+    // 文件名精确到微秒，按字典序即为时间顺序
+    let name = format!(
+        "{}{}{}",
+        SNAPSHOT_PREFIX,
+        chrono::Local::now().format("%Y%m%d-%H%M%S-%6f"),
+        SNAPSHOT_SUFFIX
+    );
+    let path = dir.join(name);
+    write_atomic(&path, &compressed)?;
+
+    prune(&storage_path, settings);
+    Ok(path)
+}
+
+/// 按数量和天数清理旧快照，最新的一个始终保留
+fn prune(storage_path: &Path, settings: &SnapshotSettings) {
+    let max_age = Duration::from_secs(settings.max_age_days * 24 * 60 * 60);
+    let now = SystemTime::now();
+
+    for (index, path) in list_snapshot_paths(storage_path).into_iter().enumerate().skip(1) {
+        let over_count = settings.max_count > 0 && index >= settings.max_count;
+        let too_old = settings.max_age_days > 0
+            && fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age > max_age);
+
+        if over_count || too_old {
+            if let Err(e) = fs::remove_file(&path) {
+                log_warn(format!("Failed to remove snapshot {}: {}", path.display(), e));
+            }
+        }
+    }
+}
+
+/// 根据 id 定位快照文件，拒绝包含路径分隔符的 id
+fn resolve_snapshot(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    if id.contains(['/', '\\']) || !id.starts_with(SNAPSHOT_PREFIX) || !id.ends_with(SNAPSHOT_SUFFIX) {
+        return Err(format!("Invalid snapshot id: {}", id));
+    }
+
+    let path = snapshot_dir(&get_storage_path(app)?).join(id);
+    if !path.exists() {
+        return Err(format!("Snapshot not found: {}", id));
+    }
+    Ok(path)
+}
+
+fn load_snapshot(app: &AppHandle, id: &str) -> Result<Storage, String> {
+    let path = resolve_snapshot(app, id)?;
+    Storage::decode_backup(app, &read_backup_file(&path)?)
+}
+
+/// 比较快照与当前数据
+pub fn diff(snapshot: &Storage, current: &Storage) -> SnapshotDiff {
+    let before: HashMap<&str, &Account> = snapshot.accounts.iter().map(|a| (a.id.as_str(), a)).collect();
+    let after: HashMap<&str, &Account> = current.accounts.iter().map(|a| (a.id.as_str(), a)).collect();
+
+    let mut result = SnapshotDiff::default();
+
+    for account in &current.accounts {
+        match before.get(account.id.as_str()) {
+            None => result.added.push(account.into()),
+            Some(old) => {
+                let fields = changed_fields(old, account);
+                if !fields.is_empty() {
+                    result.changed.push(AccountChange { account: account.into(), fields });
+                }
+            }
+        }
+    }
+
+    result.removed = snapshot.accounts.iter()
+        .filter(|a| !after.contains_key(a.id.as_str()))
+        .map(AccountSummary::from)
+        .collect();
+
+    result
+}
+
+/// 比较两个账户的顶层字段，返回不同的字段名
+fn changed_fields(old: &Account, new: &Account) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+
+    let mut fields: Vec<String> = old.keys()
+        .chain(new.keys().filter(|k| !old.contains_key(*k)))
+        .filter(|k| old.get(*k) != new.get(*k))
+        .cloned()
+        .collect();
+    fields.sort();
+    fields
+}
+
+/// 列出全部快照
+#[tauri::command]
+pub fn list_snapshots(app: AppHandle) -> Result<Vec<SnapshotInfo>, String> {
+    let storage_path = get_storage_path(&app)?;
+
+    Ok(list_snapshot_paths(&storage_path)
+        .into_iter()
+        .filter_map(|path| {
+            let metadata = fs::metadata(&path).ok()?;
+            let content = read_backup_file(&path).ok()?;
+            let encrypted = super::vault::parse_envelope(&content).is_some();
+            let account_count = Storage::decode_backup(&app, &content).ok().map(|s| s.accounts.len());
+            let created_at = metadata.modified().ok()
+                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as i64)
+                .unwrap_or(0);
+
+            Some(SnapshotInfo {
+                id: path.file_name()?.to_string_lossy().to_string(),
+                created_at,
+                size: metadata.len(),
+                encrypted,
+                account_count,
+            })
+        })
+        .collect())
+}
+
+/// 查看快照与当前数据的差异
+#[tauri::command]
+pub fn diff_snapshot(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<SnapshotDiff, AppError> {
+    let snapshot = load_snapshot(&app, &id)?;
+    let storage = state.storage()?;
+    Ok(diff(&snapshot, &storage))
+}
+
+/// 恢复快照：先为当前数据写一个快照，便于撤销
+#[tauri::command]
+pub fn restore_snapshot(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), AppError> {
+    let mut restored = load_snapshot(&app, &id)?;
+    let mut storage = state.storage()?;
+
+    if storage.load_error.is_none() {
+        write_snapshot(&app, &storage.serialize_for_disk(&app)?, &SnapshotSettings::load())?;
+    }
+
+    restored.save(&app)?;
+    log_info(format!("Restored {} accounts from snapshot {}", restored.accounts.len(), id));
+    audit::record(&app, &audit::diff_accounts(AuditAction::Restore, &storage.accounts, &restored.accounts));
+    *storage = restored;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_snapshot_settings() -> SnapshotSettings {
+    SnapshotSettings::load()
+}
+
+#[tauri::command]
+pub fn set_snapshot_settings(settings: SnapshotSettings) -> Result<(), String> {
+    settings::update(|s| s.snapshots = settings)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{self, temp_path};
+
+    fn account(id: &str, email: &str) -> Account {
+        test_support::account(id, "claude", email)
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut snapshot = Storage::new();
+        snapshot.accounts = vec![account("a", "a@example.com"), account("b", "b@example.com")];
+
+        let mut current = Storage::new();
+        let mut changed = account("a", "a@example.com");
+        changed.is_active = true;
+        current.accounts = vec![changed, account("c", "c@example.com")];
+
+        let result = diff(&snapshot, &current);
+        assert_eq!(result.added.len(), 1);
+        assert_eq!(result.added[0].id, "c");
+        assert_eq!(result.removed.len(), 1);
+        assert_eq!(result.removed[0].id, "b");
+        assert_eq!(result.changed.len(), 1);
+        assert_eq!(result.changed[0].fields, vec!["is_active".to_string()]);
+    }
+
+    #[test]
+    fn test_gz_round_trip() {
+        let path = temp_path("snapshot", ".json.gz");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"{\"accounts\":[]}").unwrap();
+        fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+        assert_eq!(read_backup_file(&path).unwrap(), "{\"accounts\":[]}");
+        let _ = fs::remove_file(path);
+    }
+}
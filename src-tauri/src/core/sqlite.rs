@@ -0,0 +1,477 @@
+//! SQLite 存储后端
+//!
+//! 将账户、机器码绑定和设置保存在本地 SQLite 数据库中（accounts.db），
+//! 单个账户的增删改只写一行，而不是重写整个 accounts.json。
+//! 数据库结构通过编号迁移（PRAGMA user_version）升级。
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use super::platform::PlatformData;
+use super::storage::{Account, DeletedAccount, Storage, SwitchRecord};
+
+/// 编号迁移：下标 + 1 即迁移后的 user_version，只能追加不能修改
+const MIGRATIONS: &[&str] = &[
+    // 1: 初始结构
+    "CREATE TABLE accounts (
+        id TEXT PRIMARY KEY,
+        position INTEGER NOT NULL,
+        platform TEXT NOT NULL,
+        name TEXT,
+        email TEXT NOT NULL,
+        avatar TEXT,
+        is_active INTEGER NOT NULL DEFAULT 0,
+        last_used_at INTEGER NOT NULL DEFAULT 0,
+        created_at INTEGER NOT NULL DEFAULT 0,
+        platform_data TEXT NOT NULL
+    );
+    CREATE INDEX idx_accounts_platform ON accounts(platform);
+    CREATE TABLE machine_bindings (
+        account_id TEXT PRIMARY KEY,
+        machine_id TEXT NOT NULL
+    );
+    CREATE TABLE settings (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );",
+    // 2: 回收站
+    "CREATE TABLE deleted_accounts (
+        id TEXT PRIMARY KEY,
+        deleted_at INTEGER NOT NULL,
+        account TEXT NOT NULL
+    );",
+    // 3: 标签、分组、备注、自定义字段（JSON 文本）
+    "ALTER TABLE accounts ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';
+    ALTER TABLE accounts ADD COLUMN group_name TEXT;
+    ALTER TABLE accounts ADD COLUMN notes TEXT;
+    ALTER TABLE accounts ADD COLUMN custom_fields TEXT NOT NULL DEFAULT '{}';",
+    // 4: 账号切换历史
+    "CREATE TABLE switch_history (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        platform TEXT NOT NULL,
+        account_id TEXT NOT NULL,
+        previous_id TEXT,
+        switched_at INTEGER NOT NULL
+    );",
+    // 5: 账户修改时间
+    "ALTER TABLE accounts ADD COLUMN updated_at INTEGER NOT NULL DEFAULT 0;",
+];
+
+/// 设置项：JSON 文件是否已导入
+const SETTING_JSON_IMPORTED: &str = "json_imported";
+/// 设置项：全局机器码
+const SETTING_MACHINE_ID: &str = "machine_id";
+
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    /// 打开数据库并执行未完成的迁移
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+
+        let conn = Connection::open(path)
+            .map_err(|e| format!("Failed to open database: {}", e))?;
+        let mut store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// 当前数据库结构版本
+    pub fn schema_version(&self) -> Result<usize, String> {
+        self.conn
+            .query_row("PRAGMA user_version", [], |row| row.get::<_, i64>(0))
+            .map(|v| v as usize)
+            .map_err(|e| format!("Failed to read schema version: {}", e))
+    }
+
+    fn migrate(&mut self) -> Result<(), String> {
+        let current = self.schema_version()?;
+        if current > MIGRATIONS.len() {
+            return Err(format!(
+                "Database schema version {} is newer than supported version {}",
+                current,
+                MIGRATIONS.len()
+            ));
+        }
+
+        for (index, sql) in MIGRATIONS.iter().enumerate().skip(current) {
+            let version = index + 1;
+            let tx = self.conn.transaction()
+                .map_err(|e| format!("Failed to start migration: {}", e))?;
+            tx.execute_batch(sql)
+                .map_err(|e| format!("Migration {} failed: {}", version, e))?;
+            tx.pragma_update(None, "user_version", version as i64)
+                .map_err(|e| format!("Migration {} failed: {}", version, e))?;
+            tx.commit()
+                .map_err(|e| format!("Migration {} failed: {}", version, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// 读取全部数据
+    pub fn load(&self) -> Result<Storage, String> {
+        let mut stmt = self.conn
+            .prepare(
+                "SELECT id, platform, name, email, avatar, is_active, last_used_at, created_at, platform_data,
+                        tags, group_name, notes, custom_fields, updated_at
+                 FROM accounts ORDER BY position",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                let platform_data: String = row.get(8)?;
+                let tags: String = row.get(9)?;
+                let custom_fields: String = row.get(12)?;
+                Ok((
+                    Account {
+                        id: row.get(0)?,
+                        platform: row.get(1)?,
+                        name: row.get(2)?,
+                        email: row.get(3)?,
+                        avatar: row.get(4)?,
+                        is_active: row.get(5)?,
+                        last_used_at: row.get(6)?,
+                        created_at: row.get(7)?,
+                        platform_data: PlatformData::default(),
+                        tags: serde_json::from_str(&tags).unwrap_or_default(),
+                        group: row.get(10)?,
+                        notes: row.get(11)?,
+                        custom_fields: serde_json::from_str(&custom_fields).unwrap_or_default(),
+                        updated_at: row.get(13)?,
+                    },
+                    platform_data,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+
+        let mut accounts = Vec::new();
+        for row in rows {
+            let (mut account, platform_data) = row.map_err(|e| e.to_string())?;
+            let platform_data = serde_json::from_str(&platform_data)
+                .map_err(|e| format!("Invalid platform_data for account {}: {}", account.id, e))?;
+            account.platform_data = PlatformData::parse_lenient(&account.platform, platform_data);
+            accounts.push(account);
+        }
+
+        let mut storage = Storage::new();
+        storage.accounts = accounts;
+        storage.machine_id = self.get_setting(SETTING_MACHINE_ID)?;
+        storage.account_machine_bindings = self.load_bindings()?;
+        storage.trash = self.load_trash()?;
+        storage.switch_history = self.load_switch_history()?;
+        Ok(storage)
+    }
+
+    fn load_switch_history(&self) -> Result<Vec<SwitchRecord>, String> {
+        let mut stmt = self.conn
+            .prepare("SELECT platform, account_id, previous_id, switched_at FROM switch_history ORDER BY seq")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(SwitchRecord {
+                    platform: row.get(0)?,
+                    account_id: row.get(1)?,
+                    previous_id: row.get(2)?,
+                    switched_at: row.get(3)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())
+    }
+
+    fn load_trash(&self) -> Result<Vec<DeletedAccount>, String> {
+        let mut stmt = self.conn
+            .prepare("SELECT account, deleted_at FROM deleted_accounts ORDER BY deleted_at")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))
+            .map_err(|e| e.to_string())?;
+
+        let mut trash = Vec::new();
+        for row in rows {
+            let (account, deleted_at) = row.map_err(|e| e.to_string())?;
+            let account = serde_json::from_str(&account)
+                .map_err(|e| format!("Invalid deleted account: {}", e))?;
+            trash.push(DeletedAccount { account, deleted_at });
+        }
+        Ok(trash)
+    }
+
+    fn load_bindings(&self) -> Result<HashMap<String, String>, String> {
+        let mut stmt = self.conn
+            .prepare("SELECT account_id, machine_id FROM machine_bindings")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?;
+
+        rows.collect::<Result<HashMap<String, String>, _>>()
+            .map_err(|e| e.to_string())
+    }
+
+    /// 整体写入（替换数据库中的全部内容）。
+    /// id 是主键，重复 id 的账户会相互覆盖，因此拒绝写入而不是静默丢弃
+    pub fn save_all(&mut self, storage: &Storage) -> Result<(), String> {
+        let mut ids = HashSet::new();
+        if let Some(account) = storage.accounts.iter().find(|a| !ids.insert(a.id.as_str())) {
+            return Err(format!("Duplicate account id {}, repair the storage first", account.id));
+        }
+
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+
+        tx.execute("DELETE FROM accounts", []).map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM machine_bindings", []).map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM deleted_accounts", []).map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM switch_history", []).map_err(|e| e.to_string())?;
+
+        for (position, account) in storage.accounts.iter().enumerate() {
+            upsert_account(&tx, account, position as i64)?;
+        }
+        for (account_id, machine_id) in &storage.account_machine_bindings {
+            tx.execute(
+                "INSERT INTO machine_bindings (account_id, machine_id) VALUES (?1, ?2)",
+                params![account_id, machine_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        for deleted in &storage.trash {
+            insert_deleted(&tx, deleted)?;
+        }
+        for record in &storage.switch_history {
+            tx.execute(
+                "INSERT INTO switch_history (platform, account_id, previous_id, switched_at) VALUES (?1, ?2, ?3, ?4)",
+                params![record.platform, record.account_id, record.previous_id, record.switched_at],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        set_setting(&tx, SETTING_MACHINE_ID, storage.machine_id.as_deref())?;
+
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    /// 写入单个账户（新增或更新），新账户追加到末尾
+    pub fn upsert_account(&self, account: &Account) -> Result<(), String> {
+        let position: i64 = self.conn
+            .query_row(
+                "SELECT COALESCE((SELECT position FROM accounts WHERE id = ?1), (SELECT COALESCE(MAX(position), -1) + 1 FROM accounts))",
+                params![account.id],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+
+        upsert_account(&self.conn, account, position)
+    }
+
+    /// 删除单个账户
+    pub fn delete_account(&self, id: &str) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM accounts WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// 将账户移入回收站（同一事务内删除账户行并写入回收站）
+    pub fn move_to_trash(&mut self, deleted: &DeletedAccount) -> Result<(), String> {
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM accounts WHERE id = ?1", params![deleted.account.id])
+            .map_err(|e| e.to_string())?;
+        insert_deleted(&tx, deleted)?;
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>, String> {
+        self.conn
+            .query_row("SELECT value FROM settings WHERE key = ?1", params![key], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    /// 是否已完成从 accounts.json 的一次性导入
+    pub fn is_json_imported(&self) -> Result<bool, String> {
+        Ok(self.get_setting(SETTING_JSON_IMPORTED)?.is_some())
+    }
+
+    /// 从 JSON 存储一次性导入，账户 id 有重复时拒绝导入（见 save_all）
+    pub fn import_json(&mut self, storage: &Storage) -> Result<(), String> {
+        self.save_all(storage)?;
+        self.mark_json_imported()
+    }
+
+    pub fn mark_json_imported(&self) -> Result<(), String> {
+        let now = chrono::Utc::now().timestamp_millis().to_string();
+        set_setting(&self.conn, SETTING_JSON_IMPORTED, Some(&now))
+    }
+}
+
+fn upsert_account(conn: &Connection, account: &Account, position: i64) -> Result<(), String> {
+    let platform_data = serde_json::to_string(&account.platform_data)
+        .map_err(|e| format!("Failed to serialize platform_data: {}", e))?;
+    let tags = serde_json::to_string(&account.tags).map_err(|e| e.to_string())?;
+    let custom_fields = serde_json::to_string(&account.custom_fields).map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO accounts (id, position, platform, name, email, avatar, is_active, last_used_at, created_at, platform_data,
+                               tags, group_name, notes, custom_fields, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+         ON CONFLICT(id) DO UPDATE SET
+            platform = excluded.platform,
+            name = excluded.name,
+            email = excluded.email,
+            avatar = excluded.avatar,
+            is_active = excluded.is_active,
+            last_used_at = excluded.last_used_at,
+            created_at = excluded.created_at,
+            platform_data = excluded.platform_data,
+            tags = excluded.tags,
+            group_name = excluded.group_name,
+            notes = excluded.notes,
+            custom_fields = excluded.custom_fields,
+            updated_at = excluded.updated_at",
+        params![
+            account.id,
+            position,
+            account.platform,
+            account.name,
+            account.email,
+            account.avatar,
+            account.is_active,
+            account.last_used_at,
+            account.created_at,
+            platform_data,
+            tags,
+            account.group,
+            account.notes,
+            custom_fields,
+            account.updated_at,
+        ],
+    )
+    .map_err(|e| format!("Failed to write account {}: {}", account.id, e))?;
+
+    Ok(())
+}
+
+fn insert_deleted(conn: &Connection, deleted: &DeletedAccount) -> Result<(), String> {
+    let account = serde_json::to_string(&deleted.account)
+        .map_err(|e| format!("Failed to serialize deleted account: {}", e))?;
+
+    conn.execute(
+        "INSERT OR REPLACE INTO deleted_accounts (id, deleted_at, account) VALUES (?1, ?2, ?3)",
+        params![deleted.account.id, deleted.deleted_at, account],
+    )
+    .map_err(|e| format!("Failed to write deleted account {}: {}", deleted.account.id, e))?;
+
+    Ok(())
+}
+
+fn set_setting(conn: &Connection, key: &str, value: Option<&str>) -> Result<(), String> {
+    match value {
+        Some(value) => conn.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        ),
+        None => conn.execute("DELETE FROM settings WHERE key = ?1", params![key]),
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::test_support::{account_with_data, temp_path};
+
+    fn temp_db() -> std::path::PathBuf {
+        temp_path("sqlite", ".db")
+    }
+
+    fn account(id: &str, email: &str) -> Account {
+        account_with_data(id, "claude", email, serde_json::json!({ "config": { "env": {} } }))
+    }
+
+    #[test]
+    fn test_migrations_applied() {
+        let path = temp_db();
+        let store = SqliteStore::open(&path).unwrap();
+        assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len());
+
+        // 重复打开不会重复执行迁移
+        drop(store);
+        assert!(SqliteStore::open(&path).is_ok());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_import_and_single_row_writes() {
+        let path = temp_db();
+        let mut store = SqliteStore::open(&path).unwrap();
+
+        let mut storage = Storage::new();
+        storage.accounts = vec![account("a", "a@example.com"), account("b", "b@example.com")];
+        storage.machine_id = Some("machine".to_string());
+        storage.account_machine_bindings.insert("a".to_string(), "m1".to_string());
+        storage.activate("b").unwrap();
+        store.import_json(&storage).unwrap();
+        assert!(store.is_json_imported().unwrap());
+
+        let mut updated = account("a", "new@example.com");
+        updated.is_active = true;
+        updated.tags = vec!["work".to_string()];
+        updated.custom_fields.insert("team".to_string(), "infra".to_string());
+        store.upsert_account(&updated).unwrap();
+        store.upsert_account(&account("c", "c@example.com")).unwrap();
+        store.delete_account("b").unwrap();
+
+        let loaded = store.load().unwrap();
+        let ids: Vec<_> = loaded.accounts.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "c"]);
+        assert_eq!(loaded.accounts[0].email, "new@example.com");
+        assert!(loaded.accounts[0].is_active);
+        assert_eq!(loaded.accounts[0].tags, vec!["work".to_string()]);
+        assert_eq!(loaded.accounts[0].custom_fields.get("team").map(String::as_str), Some("infra"));
+        assert_eq!(loaded.machine_id.as_deref(), Some("machine"));
+        assert_eq!(loaded.account_machine_bindings.get("a").map(String::as_str), Some("m1"));
+        assert_eq!(loaded.switch_history.len(), 1);
+        assert_eq!(loaded.switch_history[0].account_id, "b");
+
+        store.move_to_trash(&DeletedAccount { account: account("c", "c@example.com"), deleted_at: 1 }).unwrap();
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.accounts.len(), 1);
+        assert_eq!(loaded.trash.len(), 1);
+        assert_eq!(loaded.trash[0].account.id, "c");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_import_duplicate_ids() {
+        let path = temp_db();
+        let mut store = SqliteStore::open(&path).unwrap();
+
+        let mut storage = Storage::new();
+        storage.accounts = vec![account("a", "first@example.com"), account("a", "second@example.com")];
+
+        // 重复 id 会在主键上相互覆盖，拒绝导入
+        assert!(store.import_json(&storage).is_err());
+        assert!(!store.is_json_imported().unwrap());
+
+        // 重新分配 id 后两个账户都保留
+        assert_eq!(crate::core::consistency::reassign_duplicate_ids(&mut storage.accounts), 1);
+        store.import_json(&storage).unwrap();
+        let loaded = store.load().unwrap();
+        let emails: Vec<_> = loaded.accounts.iter().map(|a| a.email.as_str()).collect();
+        assert_eq!(emails, vec!["first@example.com", "second@example.com"]);
+        assert_eq!(loaded.accounts[0].id, "a");
+        let _ = std::fs::remove_file(path);
+    }
+}
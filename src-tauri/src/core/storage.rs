@@ -1,12 +1,26 @@
 use serde::{Deserialize, Serialize};
 
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
-use std::sync::Arc;
-use tauri::{AppHandle, Manager};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::Mutex as TokioMutex;
 use tokio::time::{sleep, Duration};
-use crate::utils::logger::{log_info, log_debug};
+use crate::utils::logger::{log_info, log_warn, log_error, log_debug};
+use super::vault::{self, VaultState, VaultStatus, VaultKey};
+use super::sqlite::SqliteStore;
+use super::migration::{self, MigrationOutcome, CURRENT_SCHEMA_VERSION};
+use super::consistency;
+use super::recovery::{self, RecoveryReport};
+use super::snapshot;
+use super::watcher;
+use super::secret_store;
+use super::settings;
+use super::vault_registry;
+use super::platform::PlatformData;
+use crate::utils::atomic_file::write_atomic;
+use crate::utils::file_lock::FileLock;
 
 // 防抖保存状态
 struct DebounceSaveState {
@@ -27,7 +41,12 @@ impl DebounceSaveState {
 static DEBOUNCE_STATE: once_cell::sync::Lazy<Arc<TokioMutex<DebounceSaveState>>> = 
     once_cell::sync::Lazy::new(|| Arc::new(TokioMutex::new(DebounceSaveState::new())));
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 当前会话的 SQLite 连接及其路径，见 with_database
+static DATABASE: once_cell::sync::Lazy<Mutex<Option<(PathBuf, SqliteStore)>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "RawAccount")]
 pub struct Account {
     pub id: String,
     pub platform: String,
@@ -37,26 +56,341 @@ pub struct Account {
     pub is_active: bool,
     pub last_used_at: i64,
     pub created_at: i64,
-    pub platform_data: serde_json::Value,
+    pub platform_data: PlatformData,
+    /// 标签
+    pub tags: Vec<String>,
+    /// 分组
+    pub group: Option<String>,
+    /// 备注
+    pub notes: Option<String>,
+    /// 自定义字段
+    pub custom_fields: BTreeMap<String, String>,
+    /// 最后修改时间（毫秒），多设备同步合并冲突时较新的一方优先。
+    /// 切换账户只改动 is_active 和 last_used_at，不更新此时间（合并时单独处理）
+    pub updated_at: i64,
+}
+
+/// 反序列化中间结构：先读取原始 JSON，再按 platform 解析 platform_data
+#[derive(Deserialize)]
+struct RawAccount {
+    id: String,
+    platform: String,
+    name: Option<String>,
+    email: String,
+    avatar: Option<String>,
+    is_active: bool,
+    last_used_at: i64,
+    created_at: i64,
+    platform_data: serde_json::Value,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    group: Option<String>,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    custom_fields: BTreeMap<String, String>,
+    #[serde(default)]
+    updated_at: i64,
+}
+
+impl From<RawAccount> for Account {
+    fn from(raw: RawAccount) -> Self {
+        let platform_data = PlatformData::parse_lenient(&raw.platform, raw.platform_data);
+        Self {
+            id: raw.id,
+            platform: raw.platform,
+            name: raw.name,
+            email: raw.email,
+            avatar: raw.avatar,
+            is_active: raw.is_active,
+            last_used_at: raw.last_used_at,
+            created_at: raw.created_at,
+            platform_data,
+            tags: raw.tags,
+            group: raw.group,
+            notes: raw.notes,
+            custom_fields: raw.custom_fields,
+            updated_at: raw.updated_at,
+        }
+    }
+}
+
+impl Account {
+    /// 严格校验 platform_data（用于前端提交的数据）
+    pub fn validate(&self) -> Result<(), String> {
+        self.platform_data.validate(&self.platform)
+    }
+}
+
+/// 账户摘要（不含 platform_data）
+#[derive(Debug, Serialize)]
+pub struct AccountSummary {
+    pub id: String,
+    pub platform: String,
+    pub email: String,
+    pub name: Option<String>,
+}
+
+impl From<&Account> for AccountSummary {
+    fn from(account: &Account) -> Self {
+        Self {
+            id: account.id.clone(),
+            platform: account.platform.clone(),
+            email: account.email.clone(),
+            name: account.name.clone(),
+        }
+    }
+}
+
+/// 回收站中的账户
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedAccount {
+    pub account: Account,
+    pub deleted_at: i64,
+}
+
+/// 回收站默认保留天数
+pub const DEFAULT_TRASH_RETENTION_DAYS: u64 = 30;
+
+/// 保存时发现存储文件已被其他进程修改（错误消息以此开头）
+pub const STORAGE_CONFLICT: &str = "Storage file was modified by another process";
+
+/// 切换历史最多保留的条数
+pub(crate) const SWITCH_HISTORY_LIMIT: usize = 200;
+
+/// 一次账号切换
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchRecord {
+    pub platform: String,
+    pub account_id: String,
+    /// 切换前该平台的激活账户
+    pub previous_id: Option<String>,
+    pub switched_at: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Storage {
+    /// 数据结构版本，见 core::migration
+    #[serde(default)]
+    pub version: u32,
+    /// 修订号：每次写入 JSON 文件时递增，用于发现其他进程的写入
+    #[serde(default)]
+    pub revision: u64,
     pub accounts: Vec<Account>,
     pub machine_id: Option<String>,
     pub account_machine_bindings: std::collections::HashMap<String, String>,
+    /// 回收站：已删除但尚未清除的账户
+    #[serde(default)]
+    pub trash: Vec<DeletedAccount>,
+    /// 账号切换历史，从旧到新
+    #[serde(default)]
+    pub switch_history: Vec<SwitchRecord>,
+    /// 锁定状态：内存中只保留不含密钥的元数据，禁止读写账户
+    #[serde(skip)]
+    pub locked: bool,
+    /// 启动时加载失败的原因；存在时禁止保存，避免用空数据覆盖原文件
+    #[serde(skip)]
+    pub load_error: Option<String>,
+    /// 密钥后端不可用（如密钥环未解锁）时的错误：部分账户中仍为引用，禁止保存，访问存储时重试
+    #[serde(skip)]
+    pub secrets_error: Option<String>,
 }
 
 impl Storage {
     pub fn new() -> Self {
         Self {
+            version: CURRENT_SCHEMA_VERSION,
+            revision: 0,
             accounts: Vec::new(),
             machine_id: None,
             account_machine_bindings: std::collections::HashMap::new(),
+            trash: Vec::new(),
+            switch_history: Vec::new(),
+            locked: false,
+            load_error: None,
+            secrets_error: None,
+        }
+    }
+
+    /// 锁定视图：丢弃所有 platform_data（Token、密钥等），仅保留元数据
+    pub fn locked_view(&self) -> Self {
+        let accounts = self.accounts.iter()
+            .map(|account| Account {
+                platform_data: PlatformData::Unknown(serde_json::Value::Null),
+                ..account.clone()
+            })
+            .collect();
+        
+        Self {
+            version: self.version,
+            revision: self.revision,
+            accounts,
+            machine_id: self.machine_id.clone(),
+            account_machine_bindings: self.account_machine_bindings.clone(),
+            trash: Vec::new(),
+            switch_history: self.switch_history.clone(),
+            locked: true,
+            load_error: None,
+            secrets_error: None,
+        }
+    }
+
+    /// 将账户设为所在平台唯一的激活账户，更新最近使用时间并记录切换历史
+    pub fn activate(&mut self, id: &str) -> Result<SwitchRecord, String> {
+        let platform = self.accounts.iter()
+            .find(|a| a.id == id)
+            .map(|a| a.platform.clone())
+            .ok_or_else(|| format!("Account {} not found", id))?;
+        let previous_id = self.active_account(&platform)
+            .map(|a| a.id.clone())
+            .filter(|previous| previous != id);
+
+        let now = chrono::Utc::now().timestamp_millis();
+        for account in self.accounts.iter_mut().filter(|a| a.platform == platform) {
+            let active = account.id == id;
+            account.is_active = active;
+            if active {
+                account.last_used_at = now;
+            }
+        }
+
+        let record = SwitchRecord {
+            platform,
+            account_id: id.to_string(),
+            previous_id,
+            switched_at: now,
+        };
+        self.switch_history.push(record.clone());
+        if self.switch_history.len() > SWITCH_HISTORY_LIMIT {
+            let excess = self.switch_history.len() - SWITCH_HISTORY_LIMIT;
+            self.switch_history.drain(..excess);
+        }
+        Ok(record)
+    }
+
+    /// 平台当前的激活账户
+    pub fn active_account(&self, platform: &str) -> Option<&Account> {
+        self.accounts.iter().find(|a| a.platform == platform && a.is_active)
+    }
+
+    /// 平台上一次切换前的激活账户（已删除的账户会被跳过）
+    pub fn previous_account(&self, platform: &str) -> Option<&Account> {
+        let current = self.active_account(platform).map(|a| a.id.as_str());
+        self.switch_history.iter()
+            .rev()
+            .filter(|r| r.platform == platform)
+            .flat_map(|r| [Some(r.account_id.as_str()), r.previous_id.as_deref()])
+            .flatten()
+            .filter(|id| Some(*id) != current)
+            .find_map(|id| self.accounts.iter().find(|a| a.id == id))
+    }
+
+    /// 将账户移入回收站，返回是否找到该账户
+    pub fn move_to_trash(&mut self, id: &str) -> bool {
+        let Some(index) = self.accounts.iter().position(|a| a.id == id) else {
+            return false;
+        };
+        
+        let account = self.accounts.remove(index);
+        self.trash.retain(|d| d.account.id != id);
+        self.trash.push(DeletedAccount {
+            account,
+            deleted_at: chrono::Utc::now().timestamp_millis(),
+        });
+        true
+    }
+
+    /// 从回收站恢复账户
+    pub fn restore_from_trash(&mut self, id: &str) -> Result<Account, String> {
+        if self.accounts.iter().any(|a| a.id == id) {
+            return Err(format!("Account {} already exists", id));
         }
+        
+        let index = self.trash.iter()
+            .position(|d| d.account.id == id)
+            .ok_or_else(|| format!("Account {} is not in the trash", id))?;
+        
+        let account = self.trash.remove(index).account;
+        self.accounts.push(account.clone());
+        Ok(account)
+    }
+
+    /// 彻底清除回收站中的账户（ids 为 None 时清空回收站），同时移除其机器码绑定。返回清除数量
+    pub fn purge_trash(&mut self, ids: Option<&[String]>) -> usize {
+        let before = self.trash.len();
+        let mut purged = Vec::new();
+        
+        self.trash.retain(|d| {
+            let matched = ids.is_none_or(|ids| ids.contains(&d.account.id));
+            if matched {
+                purged.push(d.account.id.clone());
+            }
+            !matched
+        });
+        
+        for id in &purged {
+            self.account_machine_bindings.remove(id);
+        }
+        before - self.trash.len()
+    }
+
+    /// 清除超过保留天数的回收站账户，retention_days 为 0 时不自动清除
+    pub fn purge_expired_trash(&mut self, retention_days: u64) -> usize {
+        if retention_days == 0 {
+            return 0;
+        }
+        
+        let cutoff = chrono::Utc::now().timestamp_millis() - (retention_days as i64) * 24 * 60 * 60 * 1000;
+        let expired: Vec<String> = self.trash.iter()
+            .filter(|d| d.deleted_at < cutoff)
+            .map(|d| d.account.id.clone())
+            .collect();
+        
+        if expired.is_empty() {
+            return 0;
+        }
+        self.purge_trash(Some(&expired))
+    }
+
+    /// 按配置的保留天数自动清除回收站，有清除时保存
+    pub fn auto_purge_trash(&mut self, app: &AppHandle) -> Result<(), String> {
+        if self.locked || self.load_error.is_some() {
+            return Ok(());
+        }
+        
+        let purged = self.purge_expired_trash(get_trash_retention_days());
+        if purged > 0 {
+            log_info(format!("Purged {} expired accounts from trash", purged));
+            self.save(app)?;
+        }
+        Ok(())
+    }
+
+    /// 启动或切换存储库时加载：已加密时返回锁定状态等待解锁，加载失败时记录原因并禁止保存
+    pub fn load_session(app: &AppHandle) -> Self {
+        let mut storage = Self::load(app).unwrap_or_else(|e| {
+            if e == vault::VAULT_LOCKED {
+                log_info("Storage is encrypted, waiting for unlock");
+                return Storage { locked: true, ..Storage::new() };
+            }
+            log_error(format!("Failed to load storage: {}", e));
+            Storage { load_error: Some(e), ..Storage::new() }
+        });
+        if let Err(e) = storage.auto_purge_trash(app) {
+            log_error(format!("Failed to purge trash: {}", e));
+        }
+        storage
     }
 
     pub fn load(app: &AppHandle) -> Result<Self, String> {
+        match get_storage_backend() {
+            StorageBackend::Json => Self::load_json(app),
+            StorageBackend::Sqlite => Self::load_sqlite(app),
+        }
+    }
+
+    fn load_json(app: &AppHandle) -> Result<Self, String> {
         let path = get_storage_path(app)?;
         
         if !path.exists() {
@@ -64,17 +398,259 @@ impl Storage {
             return Ok(Self::new());
         }
 
+        // 加锁失败不影响读取：文件本身通过 rename 原子替换
+        let lock = FileLock::shared(&path).ok();
         let content = fs::read_to_string(&path)
             .map_err(|e| format!("Failed to read storage: {}", e))?;
+        drop(lock);
         
-        let storage: Storage = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse storage: {}", e))?;
+        let decoded = match vault::parse_envelope(&content) {
+            Some(envelope) => match Self::decrypt(app, &envelope) {
+                // 未解锁时等待解锁；已解锁时密钥是对的，解密失败说明文件被截断或篡改
+                Err(e) if e == vault::VAULT_LOCKED => return Err(e),
+                plaintext => plaintext.and_then(|plaintext| Self::from_plaintext(&plaintext))
+                    .map(|(storage, outcome)| (storage, outcome, false)),
+            },
+            None => Self::from_plaintext(content.as_bytes())
+                .map(|(storage, outcome)| (storage, outcome, is_vault_unlocked(app))),
+        };
+        
+        let (mut storage, outcome, needs_encryption) = match decoded {
+            Ok(decoded) => decoded,
+            // 加密文件被截断后无法识别为信封：备份已加密时先等待解锁，解锁后再从备份恢复
+            Err(_) if !is_vault_unlocked(app) && newest_encrypted_backup(&path).is_some() => {
+                set_vault_status(app, VaultStatus::Locked)?;
+                return Err(vault::VAULT_LOCKED.to_string());
+            }
+            Err(e) => return Self::recover(app, &path, e),
+        };
         
         log_info(&format!("Loaded {} accounts from {}", storage.accounts.len(), path.display()));
+        watcher::mark_synced(&path, content.as_bytes(), &storage);
+
+        if storage.finish_migration(app, &path, &content, &outcome)? {
+            return Ok(storage);
+        }
+
+        // 已启用加密但文件仍为明文：原地迁移为加密格式
+        if needs_encryption {
+            log_info("Plaintext storage found while vault is unlocked, encrypting in place");
+            storage.save(app)?;
+        }
+
+        Ok(storage)
+    }
+
+    /// 文件损坏：移走损坏文件，从最新的可用备份恢复并通知前端
+    fn recover(app: &AppHandle, path: &Path, error: String) -> Result<Self, String> {
+        log_error(format!("Storage file is corrupt: {}", error));
+        
+        let corrupt_path = recovery::quarantine(path)?;
+        log_warn(format!("Corrupt storage moved to {}", corrupt_path.display()));
+        
+        // 先尝试 .bak 备份，再尝试快照
+        let candidates = recovery::find_backups(path)
+            .into_iter()
+            .chain(snapshot::list_snapshot_paths(path));
+        
+        let mut restored = None;
+        for backup in candidates {
+            let Ok(content) = snapshot::read_backup_file(&backup) else {
+                continue;
+            };
+            if let Ok(storage) = Self::decode_backup(app, &content) {
+                restored = Some((backup, storage));
+                break;
+            }
+        }
+        
+        let (restored_from, storage) = match restored {
+            Some((backup, mut storage)) => {
+                log_info(format!("Restored {} accounts from backup {}", storage.accounts.len(), backup.display()));
+                storage.save(app)?;
+                (Some(backup.to_string_lossy().to_string()), storage)
+            }
+            None => {
+                log_warn("No usable backup found, starting with empty storage");
+                (None, Self::new())
+            }
+        };
+        
+        let report = RecoveryReport {
+            corrupt_path: corrupt_path.to_string_lossy().to_string(),
+            restored_from,
+            error,
+            recovered_at: chrono::Utc::now().timestamp_millis(),
+        };
+        let _ = app.emit(recovery::STORAGE_RECOVERED_EVENT, &report);
+        recovery::set_last_recovery(report);
+        
+        Ok(storage)
+    }
+
+    /// 解析备份或快照内容；加密内容只有在已解锁时才能读取
+    pub(crate) fn decode_backup(app: &AppHandle, content: &str) -> Result<Self, String> {
+        let plaintext = match vault::parse_envelope(content) {
+            Some(envelope) if is_vault_unlocked(app) => Self::decrypt(app, &envelope)?,
+            Some(_) => return Err(vault::VAULT_LOCKED.to_string()),
+            None => content.as_bytes().to_vec(),
+        };
+        Self::from_plaintext(&plaintext).map(|(storage, _)| storage)
+    }
+
+    /// 解析 JSON 并执行结构迁移
+    fn from_plaintext(plaintext: &[u8]) -> Result<(Self, MigrationOutcome), String> {
+        let mut value: serde_json::Value = serde_json::from_slice(plaintext)
+            .map_err(|e| format!("Failed to parse storage: {}", e))?;
+        
+        let outcome = migration::migrate(&mut value)?;
+        let mut storage: Storage = serde_json::from_value(value)
+            .map_err(|e| format!("Failed to parse storage: {}", e))?;
+        secret_store::resolve(&mut storage);
+        
+        Ok((storage, outcome))
+    }
+
+    /// 迁移后处理：先备份原始文件再写回新版本。返回是否已保存
+    fn finish_migration(
+        &mut self,
+        app: &AppHandle,
+        path: &Path,
+        original: &str,
+        outcome: &MigrationOutcome,
+    ) -> Result<bool, String> {
+        match outcome {
+            MigrationOutcome::Current => Ok(false),
+            MigrationOutcome::Newer { version } => {
+                log_warn(format!(
+                    "Storage schema version {} is newer than supported version {}, saving is disabled",
+                    version, CURRENT_SCHEMA_VERSION
+                ));
+                Ok(false)
+            }
+            MigrationOutcome::Migrated { from } => {
+                let backup_path = path.with_extension(format!("json.v{}.bak", from));
+                write_atomic(&backup_path, original.as_bytes())
+                    .map_err(|e| format!("Failed to write pre-migration backup: {}", e))?;
+                log_info(format!(
+                    "Migrated storage from schema version {} to {}, backup: {}",
+                    from, CURRENT_SCHEMA_VERSION, backup_path.display()
+                ));
+                self.save(app)?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// 从 SQLite 加载；首次使用时一次性导入已有的 accounts.json
+    fn load_sqlite(app: &AppHandle) -> Result<Self, String> {
+        let db_path = get_database_path(app)?;
+        let mut storage = with_database(app, |store| {
+            if !store.is_json_imported()? {
+                let mut json_storage = Self::load_json(app)?;
+                // 旧版导入可能产生重复 id，SQLite 以 id 为主键，先重新分配以免账户相互覆盖
+                let reassigned = consistency::reassign_duplicate_ids(&mut json_storage.accounts);
+                if reassigned > 0 {
+                    log_warn(format!("Assigned new ids to {} accounts with duplicate ids before importing into SQLite", reassigned));
+                }
+                store.import_json(&secret_store::with_refs(app, &json_storage)?)?;
+                log_info(format!("Imported {} accounts from JSON into {}", json_storage.accounts.len(), db_path.display()));
+            }
+            store.load()
+        })?;
+        secret_store::resolve(&mut storage);
+        log_info(format!("Loaded {} accounts from {}", storage.accounts.len(), db_path.display()));
+        Ok(storage)
+    }
+
+    /// 使用主密码解锁加密存储，成功后 Vault 进入解锁状态。
+    /// 存储文件被截断或篡改而主密码正确时，与加载时一样从备份恢复
+    pub fn unlock(app: &AppHandle, passphrase: &str) -> Result<Self, String> {
+        let path = get_storage_path(app)?;
+        let lock = FileLock::shared(&path).ok();
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read storage: {}", e))?;
+        drop(lock);
+        
+        let envelope = vault::parse_envelope(&content);
+        if envelope.is_none() && Self::from_plaintext(content.as_bytes()).is_ok() {
+            return Err("Storage is not encrypted".to_string());
+        }
+        
+        let opened = envelope
+            .ok_or_else(|| "Encrypted storage is truncated or unreadable".to_string())
+            .and_then(|envelope| {
+                let key = VaultKey::derive(passphrase, &envelope.kdf)?;
+                let plaintext = key.open(&envelope)?;
+                Ok((key, plaintext))
+            });
+        // 解不开主文件时无法区分密码错误和文件损坏：能用同一密码解开最新的加密备份说明是后者
+        let (key, decoded) = match opened {
+            Ok((key, plaintext)) => (key, Self::from_plaintext(&plaintext)),
+            Err(e) => match verify_with_backup(&path, passphrase) {
+                Some(Ok(key)) => (key, Err(e)),
+                Some(Err(_)) => return Err("Invalid passphrase".to_string()),
+                None => return Err(e),
+            },
+        };
+        
+        set_vault_status(app, VaultStatus::Unlocked(key))?;
+        let (mut storage, outcome) = match decoded {
+            Ok(decoded) => decoded,
+            Err(e) => return Self::recover(app, &path, e),
+        };
+        watcher::mark_synced(&path, content.as_bytes(), &storage);
+        storage.finish_migration(app, &path, &content, &outcome)?;
+        
+        log_info(format!("Vault unlocked, loaded {} accounts", storage.accounts.len()));
         Ok(storage)
     }
 
-    pub fn save(&self, app: &AppHandle) -> Result<(), String> {
+    /// 解密信封；未解锁时将 Vault 标记为锁定并返回错误
+    fn decrypt(app: &AppHandle, envelope: &vault::EncryptedEnvelope) -> Result<Vec<u8>, String> {
+        let state = app.try_state::<VaultState>().ok_or("Vault state not initialized")?;
+        let mut status = state.status.lock().map_err(|e| e.to_string())?;
+        
+        match &*status {
+            VaultStatus::Unlocked(key) => key.open(envelope),
+            _ => {
+                *status = VaultStatus::Locked;
+                Err(vault::VAULT_LOCKED.to_string())
+            }
+        }
+    }
+
+    /// 保存到当前后端。JSON 文件在上次读写后被其他进程修改时返回冲突错误，
+    /// 下次访问存储时会重新读取并合并（见 core::watcher）
+    pub fn save(&mut self, app: &AppHandle) -> Result<(), String> {
+        self.write(app, true)
+    }
+
+    /// 用内存中的数据覆盖磁盘文件，不检查修订号（切换存储位置或后端时使用）
+    pub fn save_over(&mut self, app: &AppHandle) -> Result<(), String> {
+        self.write(app, false)
+    }
+
+    fn write(&mut self, app: &AppHandle, check_revision: bool) -> Result<(), String> {
+        if self.locked {
+            return Err(vault::VAULT_LOCKED.to_string());
+        }
+        self.ensure_writable_schema()?;
+        
+        if get_storage_backend() == StorageBackend::Sqlite {
+            let disk = secret_store::with_refs(app, self)?;
+            with_database(app, |store| {
+                store.save_all(&disk)?;
+                if !store.is_json_imported()? {
+                    store.mark_json_imported()?;
+                }
+                Ok(())
+            })?;
+            log_info(format!("Saved {} accounts to {}", self.accounts.len(), get_database_path(app)?.display()));
+            snapshot::after_save(app, || self.serialize_for_disk(app));
+            return Ok(());
+        }
+        
         let path = get_storage_path(app)?;
         
         // Ensure directory exists
@@ -83,16 +659,124 @@ impl Storage {
                 .map_err(|e| format!("Failed to create directory: {}", e))?;
         }
 
-        let content = serde_json::to_string_pretty(self)
-            .map_err(|e| format!("Failed to serialize storage: {}", e))?;
+        let _lock = FileLock::exclusive(&path)?;
+        let disk_revision = self.disk_revision(app, &path, check_revision)?;
+        self.revision = self.revision.max(disk_revision.unwrap_or(0)) + 1;
+        let content = self.serialize_for_disk(app)?;
+        
+        // 保留上一版文件，文件损坏时作为恢复来源
+        if path.exists() {
+            if let Err(e) = fs::copy(&path, path.with_extension("json.bak")) {
+                log_warn(format!("Failed to back up storage: {}", e));
+            }
+        }
         
-        fs::write(&path, content)
-            .map_err(|e| format!("Failed to write storage: {}", e))?;
+        write_atomic(&path, content.as_bytes())?;
+        watcher::mark_synced(&path, content.as_bytes(), self);
         
         log_info(&format!("Saved {} accounts to {}", self.accounts.len(), path.display()));
+        snapshot::after_save(app, || Ok(content));
+        Ok(())
+    }
+
+    /// 读取磁盘上文件的修订号；check 时确认文件在上次读写之后没有被其他进程修改
+    fn disk_revision(&self, app: &AppHandle, path: &Path, check: bool) -> Result<Option<u64>, String> {
+        let content = match fs::read(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Failed to read storage: {}", e)),
+        };
+        if let Some(revision) = watcher::synced_revision(path, &content) {
+            return Ok(Some(revision));
+        }
+
+        let decoded = std::str::from_utf8(&content)
+            .map_err(|e| e.to_string())
+            .and_then(|text| Self::decode_backup(app, text));
+        if !check {
+            return Ok(decoded.ok().map(|disk| disk.revision));
+        }
+        match decoded {
+            // 没有读写记录（如刚切换了存储位置）且修订号一致，视为同一份数据
+            Ok(disk) if !watcher::has_synced(path) && disk.revision == self.revision => Ok(Some(disk.revision)),
+            Ok(disk) => Err(format!(
+                "{} (revision {} on disk, {} in memory)",
+                STORAGE_CONFLICT, disk.revision, self.revision
+            )),
+            Err(e) => Err(format!("{}: {}", STORAGE_CONFLICT, e)),
+        }
+    }
+
+    /// 序列化为写入磁盘的内容（启用加密时为密文信封）
+    pub(crate) fn serialize_for_disk(&self, app: &AppHandle) -> Result<String, String> {
+        let disk = secret_store::with_refs(app, self)?;
+        let content = serde_json::to_string_pretty(&disk)
+            .map_err(|e| format!("Failed to serialize storage: {}", e))?;
+        encrypt_for_disk(app, content)
+    }
+
+    /// 拒绝用旧结构覆盖由更新版本写入的文件，避免丢失新字段
+    fn ensure_writable_schema(&self) -> Result<(), String> {
+        if let Some(error) = &self.load_error {
+            return Err(format!("Storage failed to load ({}), refusing to overwrite it", error));
+        }
+        if let Some(error) = &self.secrets_error {
+            return Err(format!("Secret backend is unavailable ({}), storage is read-only", error));
+        }
+        if self.version > CURRENT_SCHEMA_VERSION {
+            return Err(format!(
+                "Storage was written by a newer version of the app (schema {}), refusing to overwrite it with schema {}",
+                self.version, CURRENT_SCHEMA_VERSION
+            ));
+        }
         Ok(())
     }
 
+    /// 保存单个账户：SQLite 后端只写一行，JSON 后端整体保存
+    pub fn save_account(&mut self, app: &AppHandle, account: &Account) -> Result<(), String> {
+        if self.locked {
+            return Err(vault::VAULT_LOCKED.to_string());
+        }
+        self.ensure_writable_schema()?;
+        
+        match get_storage_backend() {
+            StorageBackend::Json => self.save(app),
+            StorageBackend::Sqlite => {
+                let account = secret_store::account_with_refs(app, account)?;
+                with_database(app, |store| store.upsert_account(&account))?;
+                snapshot::after_save(app, || self.serialize_for_disk(app));
+                Ok(())
+            }
+        }
+    }
+
+    /// 删除单个账户（调用前已移入回收站）：SQLite 后端只改动该账户的行，JSON 后端整体保存
+    pub fn remove_account(&mut self, app: &AppHandle, id: &str) -> Result<(), String> {
+        if self.locked {
+            return Err(vault::VAULT_LOCKED.to_string());
+        }
+        self.ensure_writable_schema()?;
+        
+        match get_storage_backend() {
+            StorageBackend::Json => self.save(app),
+            StorageBackend::Sqlite => {
+                let deleted = match self.trash.iter().find(|d| d.account.id == id) {
+                    Some(deleted) => Some(DeletedAccount {
+                        account: secret_store::account_with_refs(app, &deleted.account)?,
+                        ..deleted.clone()
+                    }),
+                    None => None,
+                };
+                with_database(app, |store| match &deleted {
+                    Some(deleted) => store.move_to_trash(deleted),
+                    None => store.delete_account(id),
+                })?;
+                snapshot::after_save(app, || self.serialize_for_disk(app));
+                Ok(())
+            }
+        }
+    }
+
     /// 防抖保存 - 300ms 内的多次修改只触发一次写入
     /// 
     /// 使用场景：批量操作、频繁更新
@@ -101,7 +785,7 @@ impl Storage {
         const DEBOUNCE_MS: u64 = 300;
         
         let state = DEBOUNCE_STATE.clone();
-        let storage_clone = self.clone();
+        let mut storage_clone = self.clone();
         
         // 标记有待保存的更改
         {
@@ -134,74 +818,109 @@ impl Storage {
     }
 }
 
-// Global configuration state for custom path
-pub struct StorageConfig {
-    pub custom_path: std::sync::Mutex<Option<PathBuf>>,
+/// 根据 Vault 状态决定写入明文还是加密信封
+fn encrypt_for_disk(app: &AppHandle, content: String) -> Result<String, String> {
+    let Some(state) = app.try_state::<VaultState>() else {
+        return Ok(content);
+    };
+    let status = state.status.lock().map_err(|e| e.to_string())?;
+    
+    match &*status {
+        VaultStatus::Plain => Ok(content),
+        // 锁定状态下内存中没有真实数据，绝不能覆盖磁盘上的加密文件
+        VaultStatus::Locked => Err(vault::VAULT_LOCKED.to_string()),
+        VaultStatus::Unlocked(key) => {
+            let envelope = key.seal(content.as_bytes())?;
+            serde_json::to_string_pretty(&envelope)
+                .map_err(|e| format!("Failed to serialize vault: {}", e))
+        }
+    }
 }
 
-/// 获取配置文件路径
-fn get_config_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    
-    Ok(app_dir.join("config.json"))
+/// 最新的加密备份（按修改时间从新到旧第一个能解析为信封的 .bak 文件）
+fn newest_encrypted_backup(path: &Path) -> Option<vault::EncryptedEnvelope> {
+    recovery::find_backups(path)
+        .into_iter()
+        .filter_map(|backup| snapshot::read_backup_file(&backup).ok())
+        .find_map(|content| vault::parse_envelope(&content))
 }
 
-/// 从配置文件加载自定义路径
-fn load_custom_path_from_config(app: &AppHandle) -> Option<PathBuf> {
-    let config_path = get_config_path(app).ok()?;
-    
-    if !config_path.exists() {
-        return None;
-    }
-    
-    let content = fs::read_to_string(&config_path).ok()?;
-    let config: serde_json::Value = serde_json::from_str(&content).ok()?;
-    
-    config.get("storage_path")
-        .and_then(|v| v.as_str())
-        .map(PathBuf::from)
+/// 用最新的加密备份验证主密码，能解开时返回其密钥；没有加密备份时返回 None
+fn verify_with_backup(path: &Path, passphrase: &str) -> Option<Result<VaultKey, String>> {
+    let envelope = newest_encrypted_backup(path)?;
+    Some(VaultKey::derive(passphrase, &envelope.kdf).and_then(|key| {
+        key.open(&envelope)?;
+        Ok(key)
+    }))
 }
 
-/// 保存自定义路径到配置文件
-fn save_custom_path_to_config(app: &AppHandle, path: Option<&PathBuf>) -> Result<(), String> {
-    let config_path = get_config_path(app)?;
-    
-    // Ensure directory exists
-    if let Some(parent) = config_path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
-    }
-    
-    let mut config = if config_path.exists() {
-        let content = fs::read_to_string(&config_path)
-            .map_err(|e| format!("Failed to read config: {}", e))?;
-        serde_json::from_str(&content).unwrap_or(serde_json::json!({}))
-    } else {
-        serde_json::json!({})
+pub(crate) fn is_vault_unlocked(app: &AppHandle) -> bool {
+    app.try_state::<VaultState>()
+        .and_then(|state| state.status.lock().ok().map(|s| matches!(*s, VaultStatus::Unlocked(_))))
+        .unwrap_or(false)
+}
+
+/// 更新 Vault 状态，返回之前的状态
+pub fn set_vault_status(app: &AppHandle, new_status: VaultStatus) -> Result<VaultStatus, String> {
+    let state = app.try_state::<VaultState>().ok_or("Vault state not initialized")?;
+    let mut status = state.status.lock().map_err(|e| e.to_string())?;
+    Ok(std::mem::replace(&mut *status, new_status))
+}
+
+/// 存储后端
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// accounts.json（默认，支持加密）
+    #[default]
+    Json,
+    /// accounts.db（SQLite，单行写入）
+    Sqlite,
+}
+
+/// 从设置读取存储后端，默认 JSON
+pub fn get_storage_backend() -> StorageBackend {
+    settings::load().storage_backend
+}
+
+/// SQLite 数据库路径：与 accounts.json 同目录
+fn get_database_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_storage_path(app)?.with_extension("db"))
+}
+
+/// 使用当前存储位置的 SQLite 连接执行 f。连接在会话内复用，避免每次写入都重新打开数据库并检查迁移；
+/// 存储位置变化（切换存储位置或命名存储库）时重新打开
+fn with_database<T>(app: &AppHandle, f: impl FnOnce(&mut SqliteStore) -> Result<T, String>) -> Result<T, String> {
+    let path = get_database_path(app)?;
+    let mut cached = DATABASE.lock().map_err(|e| e.to_string())?;
+    let current = match cached.take() {
+        Some((cached_path, store)) if cached_path == path => (cached_path, store),
+        _ => (path.clone(), SqliteStore::open(&path)?),
     };
-    
-    if let Some(path) = path {
-        config["storage_path"] = serde_json::json!(path.to_string_lossy().to_string());
-    } else {
-        // Remove storage_path key if path is None
-        if let Some(obj) = config.as_object_mut() {
-            obj.remove("storage_path");
-        }
+    let (_, store) = cached.insert(current);
+    f(store)
+}
+
+// Global configuration state for custom path
+pub struct StorageConfig {
+    pub custom_path: std::sync::Mutex<Option<PathBuf>>,
+}
+
+/// 从设置加载自定义路径：已打开命名存储库时为其路径
+fn load_custom_path_from_config() -> Option<PathBuf> {
+    if let Some(vault) = vault_registry::current_vault() {
+        return Some(vault.path);
     }
-    
-    let content = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    
-    fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write config: {}", e))?;
-    
+    settings::load().storage_path
+}
+
+/// 保存自定义路径到设置
+pub(crate) fn save_custom_path_to_config(path: Option<&PathBuf>) -> Result<(), String> {
+    settings::update(|s| s.storage_path = path.cloned())?;
     Ok(())
 }
 
-fn get_storage_path(app: &AppHandle) -> Result<PathBuf, String> {
+pub(crate) fn get_storage_path(app: &AppHandle) -> Result<PathBuf, String> {
     // Check if custom path is set in state
     if let Some(state) = app.try_state::<StorageConfig>() {
         if let Ok(custom_path) = state.custom_path.lock() {
@@ -212,7 +931,7 @@ fn get_storage_path(app: &AppHandle) -> Result<PathBuf, String> {
     }
     
     // Try to load from config file
-    if let Some(path) = load_custom_path_from_config(app) {
+    if let Some(path) = load_custom_path_from_config() {
         // Update state with loaded path
         if let Some(state) = app.try_state::<StorageConfig>() {
             if let Ok(mut custom_path) = state.custom_path.lock() {
@@ -222,7 +941,19 @@ fn get_storage_path(app: &AppHandle) -> Result<PathBuf, String> {
         return Ok(path);
     }
 
-    // Default path
+    default_storage_path(app)
+}
+
+/// 默认存储库的路径：set_storage_path 设置的路径，未设置时为应用数据目录下的 accounts.json
+pub(crate) fn default_storage_path(app: &AppHandle) -> Result<PathBuf, String> {
+    if let Some(path) = settings::load().storage_path {
+        return Ok(path);
+    }
+    app_data_storage_path(app)
+}
+
+/// 应用数据目录下的 accounts.json
+pub(crate) fn app_data_storage_path(app: &AppHandle) -> Result<PathBuf, String> {
     let app_dir = app
         .path()
         .app_data_dir()
@@ -231,48 +962,6 @@ fn get_storage_path(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(app_dir.join("accounts.json"))
 }
 
-#[tauri::command]
-pub fn set_storage_path(
-    app: AppHandle,
-    path: String,
-    state: tauri::State<'_, StorageConfig>
-) -> Result<(), String> {
-    let path_buf = if path.is_empty() {
-        // Empty path means reset to default
-        None
-    } else {
-        let pb = PathBuf::from(&path);
-        
-        // Validate path
-        if let Some(parent) = pb.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent)
-                    .map_err(|e| format!("Failed to create directory: {}", e))?;
-            }
-        }
-        
-        Some(pb)
-    };
-    
-    // Save to config file first
-    save_custom_path_to_config(&app, path_buf.as_ref())?;
-    
-    // Update state (释放锁在这个作用域结束时)
-    {
-        let mut custom_path = state.custom_path.lock().map_err(|e| e.to_string())?;
-        *custom_path = path_buf.clone();
-    } // 锁在这里被释放
-    
-    // Save current storage to new location (现在可以安全调用 save，因为锁已释放)
-    if let Some(app_state) = app.try_state::<crate::commands::AppState>() {
-        let storage = app_state.storage.lock().map_err(|e| e.to_string())?;
-        storage.save(&app)?;
-    }
-    
-    log_info(&format!("Storage path updated to: {:?}", path_buf));
-    Ok(())
-}
-
 #[tauri::command]
 pub fn get_current_storage_path(app: AppHandle) -> Result<String, String> {
     let path = get_storage_path(&app)?;
@@ -280,6 +969,7 @@ pub fn get_current_storage_path(app: AppHandle) -> Result<String, String> {
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "applog", skip_all)]
 pub async fn select_storage_directory(app: AppHandle) -> Result<Option<String>, String> {
     use tauri_plugin_dialog::DialogExt;
     
@@ -298,3 +988,46 @@ pub async fn select_storage_directory(app: AppHandle) -> Result<Option<String>,
 }
 
 
+
+/// 回收站保留天数（设置项 trash_retention_days）
+pub fn get_trash_retention_days() -> u64 {
+    settings::load().trash_retention_days
+}
+
+#[tauri::command]
+pub fn get_current_storage_backend() -> StorageBackend {
+    get_storage_backend()
+}
+
+/// 切换存储后端，并把当前内存中的数据写入新后端
+#[tauri::command]
+pub fn set_storage_backend(app: AppHandle, backend: StorageBackend) -> Result<(), String> {
+    if get_storage_backend() == backend {
+        return Ok(());
+    }
+    
+    if backend == StorageBackend::Sqlite {
+        let state = app.try_state::<VaultState>().ok_or("Vault state not initialized")?;
+        let status = state.status.lock().map_err(|e| e.to_string())?;
+        if !matches!(*status, VaultStatus::Plain) {
+            return Err("SQLite backend is not available while vault encryption is enabled".to_string());
+        }
+    }
+    
+    let app_state = app.try_state::<crate::commands::AppState>().ok_or("App state not initialized")?;
+    let mut storage = app_state.storage.lock().map_err(|e| e.to_string())?;
+    if storage.locked {
+        return Err(vault::VAULT_LOCKED.to_string());
+    }
+    
+    let previous = get_storage_backend();
+    settings::update(|s| s.storage_backend = backend)?;
+    
+    if let Err(e) = storage.save_over(&app) {
+        settings::update(|s| s.storage_backend = previous)?;
+        return Err(e);
+    }
+    
+    log_info(format!("Storage backend switched to {:?}", backend));
+    Ok(())
+}
@@ -0,0 +1,42 @@
+//! 测试共用的账户、存储和临时路径
+
+use serde_json::Value;
+use std::path::PathBuf;
+
+use super::platform::PlatformData;
+use super::storage::{Account, Storage};
+
+/// 只有 id、平台和邮箱的账户，其余字段为默认值
+pub fn account(id: &str, platform: &str, email: &str) -> Account {
+    Account {
+        id: id.to_string(),
+        platform: platform.to_string(),
+        email: email.to_string(),
+        ..Default::default()
+    }
+}
+
+/// 同 account，平台数据由 data 宽松解析
+pub fn account_with_data(id: &str, platform: &str, email: &str, data: Value) -> Account {
+    Account {
+        platform_data: PlatformData::parse_lenient(platform, data),
+        ..account(id, platform, email)
+    }
+}
+
+/// 只包含给定账户的存储
+pub fn storage(accounts: Vec<Account>) -> Storage {
+    Storage { accounts, ..Storage::new() }
+}
+
+/// 系统临时目录下不重复的路径 `nexus-<name>-<uuid><suffix>`，不创建文件
+pub fn temp_path(name: &str, suffix: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("nexus-{}-{}{}", name, uuid::Uuid::new_v4(), suffix))
+}
+
+/// 新建一个不重复的临时目录
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = temp_path(name, "");
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
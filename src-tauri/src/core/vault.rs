@@ -0,0 +1,289 @@
+//! 账户数据加密（Vault）
+//!
+//! 使用 Argon2id 从主密码派生密钥，ChaCha20-Poly1305 进行认证加密。
+//! 加密后的 accounts.json 是一个带版本号和 KDF 参数的 JSON 信封。
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose, Engine as _};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use zeroize::Zeroizing;
+
+/// 信封格式标识
+pub const ENVELOPE_FORMAT: &str = "nexus-vault";
+
+/// 信封格式版本
+pub const ENVELOPE_VERSION: u32 = 1;
+
+/// 存储被锁定时返回的错误信息
+pub const VAULT_LOCKED: &str = "Vault is locked";
+
+/// Argon2id 默认参数（OWASP 推荐值：19 MiB, 2 次迭代, 1 并行度）
+const DEFAULT_MEMORY_KIB: u32 = 19 * 1024;
+const DEFAULT_ITERATIONS: u32 = 2;
+const DEFAULT_PARALLELISM: u32 = 1;
+
+/// 允许的 Argon2id 参数上限：信封和导出包可能来自不可信的文件，
+/// 过大的参数会导致派生密钥时耗尽内存或长时间占用 CPU
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 10;
+const MAX_PARALLELISM: u32 = 8;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+/// 密钥派生参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub algorithm: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    /// Base64 编码的盐值
+    pub salt: String,
+}
+
+impl KdfParams {
+    /// 使用默认参数和随机盐值创建
+    pub fn generate() -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+
+        Self {
+            algorithm: "argon2id".to_string(),
+            memory_kib: DEFAULT_MEMORY_KIB,
+            iterations: DEFAULT_ITERATIONS,
+            parallelism: DEFAULT_PARALLELISM,
+            salt: general_purpose::STANDARD.encode(salt),
+        }
+    }
+
+    /// 拒绝超出上限的参数
+    fn check_limits(&self) -> Result<(), String> {
+        if self.memory_kib > MAX_MEMORY_KIB || self.iterations > MAX_ITERATIONS || self.parallelism > MAX_PARALLELISM {
+            return Err(format!(
+                "KDF parameters out of range (memory {} KiB, {} iterations, {} lanes; at most {} KiB, {} iterations, {} lanes)",
+                self.memory_kib, self.iterations, self.parallelism, MAX_MEMORY_KIB, MAX_ITERATIONS, MAX_PARALLELISM
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 加密信封（写入磁盘的格式）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedEnvelope {
+    pub format: String,
+    pub version: u32,
+    pub kdf: KdfParams,
+    /// Base64 编码的 nonce
+    pub nonce: String,
+    /// Base64 编码的密文（包含认证标签）
+    pub ciphertext: String,
+}
+
+/// 派生后的密钥，离开作用域时自动清零
+pub struct VaultKey {
+    key: Zeroizing<[u8; KEY_LEN]>,
+    kdf: KdfParams,
+}
+
+impl VaultKey {
+    /// 从主密码和指定 KDF 参数派生密钥
+    pub fn derive(passphrase: &str, kdf: &KdfParams) -> Result<Self, String> {
+        if kdf.algorithm != "argon2id" {
+            return Err(format!("Unsupported KDF algorithm: {}", kdf.algorithm));
+        }
+        kdf.check_limits()?;
+
+        let salt = general_purpose::STANDARD
+            .decode(&kdf.salt)
+            .map_err(|e| format!("Invalid KDF salt: {}", e))?;
+
+        let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(KEY_LEN))
+            .map_err(|e| format!("Invalid KDF parameters: {}", e))?;
+
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, key.as_mut())
+            .map_err(|e| format!("Key derivation failed: {}", e))?;
+
+        Ok(Self {
+            key,
+            kdf: kdf.clone(),
+        })
+    }
+
+    /// 使用新的随机盐值派生密钥（启用加密或修改密码时使用）
+    pub fn generate(passphrase: &str) -> Result<Self, String> {
+        if passphrase.is_empty() {
+            return Err("Passphrase must not be empty".to_string());
+        }
+        Self::derive(passphrase, &KdfParams::generate())
+    }
+
+    /// 检查主密码是否与当前密钥匹配
+    pub fn matches(&self, passphrase: &str) -> bool {
+        match Self::derive(passphrase, &self.kdf) {
+            Ok(other) => other.key.as_ref() == self.key.as_ref(),
+            Err(_) => false,
+        }
+    }
+
+    /// 加密数据
+    pub fn seal(&self, plaintext: &[u8]) -> Result<EncryptedEnvelope, String> {
+        self.seal_as(ENVELOPE_FORMAT, plaintext)
+    }
+
+    /// 以指定格式标识加密数据（格式标识参与认证，如加密导出包）
+    pub fn seal_as(&self, format: &str, plaintext: &[u8]) -> Result<EncryptedEnvelope, String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(self.key.as_ref()));
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad: &associated_data(format, ENVELOPE_VERSION) })
+            .map_err(|_| "Encryption failed".to_string())?;
+
+        Ok(EncryptedEnvelope {
+            format: format.to_string(),
+            version: ENVELOPE_VERSION,
+            kdf: self.kdf.clone(),
+            nonce: general_purpose::STANDARD.encode(nonce),
+            ciphertext: general_purpose::STANDARD.encode(ciphertext),
+        })
+    }
+
+    /// 解密数据
+    pub fn open(&self, envelope: &EncryptedEnvelope) -> Result<Vec<u8>, String> {
+        if envelope.version > ENVELOPE_VERSION {
+            return Err(format!("Unsupported vault version: {}", envelope.version));
+        }
+
+        let nonce = general_purpose::STANDARD
+            .decode(&envelope.nonce)
+            .map_err(|e| format!("Invalid nonce: {}", e))?;
+        if nonce.len() != NONCE_LEN {
+            return Err("Invalid nonce length".to_string());
+        }
+
+        let ciphertext = general_purpose::STANDARD
+            .decode(&envelope.ciphertext)
+            .map_err(|e| format!("Invalid ciphertext: {}", e))?;
+
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(self.key.as_ref()));
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: &associated_data(&envelope.format, envelope.version) })
+            .map_err(|_| "Invalid passphrase or corrupted data".to_string())
+    }
+}
+
+/// 附加认证数据：绑定格式标识和版本，防止信封头被篡改
+fn associated_data(format: &str, version: u32) -> Vec<u8> {
+    format!("{}:v{}", format, version).into_bytes()
+}
+
+/// 尝试将文件内容解析为加密信封，明文存储返回 None
+pub fn parse_envelope(content: &str) -> Option<EncryptedEnvelope> {
+    parse_envelope_as(content, ENVELOPE_FORMAT)
+}
+
+/// 尝试将内容解析为指定格式标识的加密信封
+pub fn parse_envelope_as(content: &str, format: &str) -> Option<EncryptedEnvelope> {
+    let envelope: EncryptedEnvelope = serde_json::from_str(content).ok()?;
+    if envelope.format == format {
+        Some(envelope)
+    } else {
+        None
+    }
+}
+
+/// Vault 当前状态
+pub enum VaultStatus {
+    /// 明文存储
+    Plain,
+    /// 存储已加密但尚未解锁
+    Locked,
+    /// 已解锁，保存时使用该密钥加密
+    Unlocked(VaultKey),
+}
+
+/// 默认空闲自动锁定时间（分钟）
+pub const DEFAULT_AUTO_LOCK_MINUTES: u64 = 15;
+
+/// Vault 运行时状态（由 Tauri 管理）
+pub struct VaultState {
+    pub status: Mutex<VaultStatus>,
+    /// 空闲多少分钟后自动锁定，0 表示不自动锁定
+    pub auto_lock_minutes: Mutex<u64>,
+}
+
+impl VaultState {
+    pub fn new(auto_lock_minutes: u64) -> Self {
+        Self {
+            status: Mutex::new(VaultStatus::Plain),
+            auto_lock_minutes: Mutex::new(auto_lock_minutes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let key = VaultKey::generate("correct horse").unwrap();
+        let envelope = key.seal(b"{\"accounts\":[]}").unwrap();
+
+        assert_eq!(envelope.format, ENVELOPE_FORMAT);
+        assert_eq!(key.open(&envelope).unwrap(), b"{\"accounts\":[]}");
+    }
+
+    #[test]
+    fn test_wrong_passphrase_fails() {
+        let key = VaultKey::generate("correct horse").unwrap();
+        let envelope = key.seal(b"secret").unwrap();
+
+        let wrong = VaultKey::derive("battery staple", &envelope.kdf).unwrap();
+        assert!(wrong.open(&envelope).is_err());
+        assert!(key.matches("correct horse"));
+        assert!(!key.matches("battery staple"));
+    }
+
+    #[test]
+    fn test_kdf_limits() {
+        let mut kdf = KdfParams::generate();
+        kdf.memory_kib = 4 * 1024 * 1024;
+        assert!(VaultKey::derive("pass", &kdf).is_err());
+
+        let kdf = KdfParams { iterations: 1000, ..KdfParams::generate() };
+        assert!(VaultKey::derive("pass", &kdf).is_err());
+        let kdf = KdfParams { parallelism: 64, ..KdfParams::generate() };
+        assert!(VaultKey::derive("pass", &kdf).is_err());
+    }
+
+    #[test]
+    fn test_parse_envelope() {
+        let key = VaultKey::generate("pass").unwrap();
+        let content = serde_json::to_string(&key.seal(b"data").unwrap()).unwrap();
+
+        assert!(parse_envelope(&content).is_some());
+        assert!(parse_envelope("{\"accounts\":[],\"machine_id\":null}").is_none());
+    }
+
+    #[test]
+    fn test_format_is_authenticated() {
+        let key = VaultKey::generate("pass").unwrap();
+        let mut envelope = key.seal_as("nexus-export-bundle", b"data").unwrap();
+        assert_eq!(key.open(&envelope).unwrap(), b"data");
+
+        // 篡改格式标识后无法解密
+        envelope.format = ENVELOPE_FORMAT.to_string();
+        assert!(key.open(&envelope).is_err());
+    }
+}
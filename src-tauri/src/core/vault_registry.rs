@@ -0,0 +1,198 @@
+//! 命名存储库
+//!
+//! 设置中的 `vaults` 记录各存储库的名称和 accounts.json 路径（如工作账户和个人账户分开保存），
+//! `current_vault` 记录当前打开的存储库。打开存储库即切换存储路径并重新加载 AppState.storage，无需重启。
+//! 机器码绑定、回收站、快照、审计日志都保存在各自的存储文件或目录中，互不影响。
+//! 未命名的默认存储库使用 set_storage_path 设置的路径。每个存储库可以分别加密（见 core::vault）。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::settings;
+use super::storage::{default_storage_path, get_storage_path, set_vault_status, Storage, StorageConfig};
+use super::vault::VaultStatus;
+use crate::commands::AppState;
+use crate::core::error::AppError;
+use crate::utils::atomic_file::write_atomic;
+use crate::utils::logger::log_info;
+
+/// 默认存储库名称
+pub const DEFAULT_VAULT: &str = "default";
+
+/// 切换存储库完成事件
+pub const VAULT_OPENED_EVENT: &str = "vault-opened";
+
+/// 已登记的存储库
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultEntry {
+    pub name: String,
+    /// accounts.json 的路径
+    pub path: PathBuf,
+    pub created_at: i64,
+}
+
+/// 存储库信息（返回给前端）
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultInfo {
+    pub name: String,
+    pub path: String,
+    pub is_open: bool,
+    /// 存储文件是否存在（可能在其他设备上被移走）
+    pub exists: bool,
+}
+
+/// 当前打开的命名存储库，打开的是默认存储库时为 None
+pub fn current_vault() -> Option<VaultEntry> {
+    let settings = settings::load();
+    let name = settings.current_vault?;
+    settings.vaults.into_iter().find(|v| v.name == name)
+}
+
+fn validate_name(name: &str, vaults: &[VaultEntry]) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Vault name cannot be empty".to_string());
+    }
+    if name == DEFAULT_VAULT || vaults.iter().any(|v| v.name == name) {
+        return Err(format!("Vault '{}' already exists", name));
+    }
+    Ok(())
+}
+
+/// 由名称生成目录名，只保留字母、数字、`-` 和 `_`
+fn dir_name(name: &str) -> String {
+    let sanitized: String = name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}-{}", sanitized, &uuid::Uuid::new_v4().simple().to_string()[..8])
+}
+
+fn info(app: &AppHandle, name: &str, path: PathBuf) -> VaultInfo {
+    let is_open = get_storage_path(app).is_ok_and(|current| current == path);
+    VaultInfo {
+        name: name.to_string(),
+        exists: path.exists(),
+        path: path.to_string_lossy().to_string(),
+        is_open,
+    }
+}
+
+/// 列出默认存储库和所有命名存储库
+#[tauri::command]
+pub fn list_vaults(app: AppHandle) -> Result<Vec<VaultInfo>, String> {
+    let mut vaults = vec![info(&app, DEFAULT_VAULT, default_storage_path(&app)?)];
+    vaults.extend(settings::load().vaults.into_iter().map(|v| info(&app, &v.name, v.path)));
+    Ok(vaults)
+}
+
+/// 登记新的存储库；path 为空时在应用数据目录下新建，指向已有文件时直接使用该文件
+#[tauri::command]
+pub fn create_vault(app: AppHandle, name: String, path: Option<String>) -> Result<VaultInfo, String> {
+    let name = name.trim().to_string();
+    let vaults = settings::load().vaults;
+    validate_name(&name, &vaults)?;
+
+    let path = match path.filter(|p| !p.is_empty()).map(PathBuf::from) {
+        Some(path) if path.is_dir() => path.join("accounts.json"),
+        Some(path) => path,
+        None => app.path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?
+            .join("vaults")
+            .join(dir_name(&name))
+            .join("accounts.json"),
+    };
+    if vaults.iter().any(|v| v.path == path) || default_storage_path(&app)? == path {
+        return Err(format!("{} is already registered as a vault", path.display()));
+    }
+
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(&Storage::new())
+            .map_err(|e| format!("Failed to serialize storage: {}", e))?;
+        write_atomic(&path, content.as_bytes())?;
+    }
+
+    let entry = VaultEntry {
+        name: name.clone(),
+        path: path.clone(),
+        created_at: chrono::Utc::now().timestamp_millis(),
+    };
+    settings::update(|s| s.vaults.push(entry))?;
+
+    log_info(format!("Created vault '{}' at {}", name, path.display()));
+    Ok(info(&app, &name, path))
+}
+
+/// 打开存储库，替换内存中的账户数据
+#[tauri::command]
+pub fn open_vault(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    config: State<'_, StorageConfig>,
+    name: String,
+) -> Result<VaultInfo, AppError> {
+    let current = if name == DEFAULT_VAULT {
+        None
+    } else {
+        let vault = settings::load().vaults.into_iter()
+            .find(|v| v.name == name)
+            .ok_or_else(|| format!("Vault '{}' not found", name))?;
+        Some(vault.name)
+    };
+
+    // 持有存储锁完成切换，期间其他命令不会写入任何一个存储库
+    let mut storage = state.storage.lock().unwrap();
+    settings::update(|s| s.current_vault = current)?;
+    // 清空缓存的路径，下次读取时按 current_vault 重新解析
+    *config.custom_path.lock().map_err(|e| e.to_string())? = None;
+    // 丢弃上一个存储库的密钥，新存储库已加密时需要重新解锁
+    set_vault_status(&app, VaultStatus::Plain)?;
+
+    *storage = Storage::load_session(&app);
+    drop(storage);
+    state.touch();
+
+    let opened = info(&app, &name, get_storage_path(&app)?);
+    log_info(format!("Opened vault '{}' at {}", opened.name, opened.path));
+    let _ = app.emit(VAULT_OPENED_EVENT, &opened);
+    Ok(opened)
+}
+
+/// 关闭当前的命名存储库，回到默认存储库
+#[tauri::command]
+pub fn close_vault(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    config: State<'_, StorageConfig>,
+) -> Result<VaultInfo, AppError> {
+    if current_vault().is_none() {
+        return Err("No named vault is open".into());
+    }
+    open_vault(app, state, config, DEFAULT_VAULT.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        let vaults = vec![VaultEntry { name: "work".to_string(), path: PathBuf::from("/w/accounts.json"), created_at: 0 }];
+        assert!(validate_name("personal", &vaults).is_ok());
+        assert!(validate_name("work", &vaults).is_err());
+        assert!(validate_name(DEFAULT_VAULT, &vaults).is_err());
+        assert!(validate_name("", &vaults).is_err());
+    }
+
+    #[test]
+    fn test_dir_name() {
+        let dir = dir_name("Work / Client A");
+        assert!(dir.starts_with("Work___Client_A-"));
+        assert_eq!(dir.len(), "Work___Client_A-".len() + 8);
+    }
+}
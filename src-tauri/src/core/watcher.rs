@@ -0,0 +1,430 @@
+//! 存储文件外部修改检测与合并
+//!
+//! 存储目录可能由 Syncthing、Nextcloud 等同步到多台机器。后台定时检查 accounts.json
+//! 的修改时间和大小，访问存储前也会检查一次；发现外部修改时，以上次读写磁盘时的内容为基准，
+//! 与内存中的数据做三方合并，写回后通过 `storage-changed` 事件通知前端刷新。
+//! 双方都修改了同一账户时，updated_at 较新的一方优先，并在报告中列出冲突。
+//! 仅适用于 JSON 存储后端。
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::storage::{
+    get_storage_backend, get_storage_path, Account, AccountSummary, Storage, StorageBackend, SWITCH_HISTORY_LIMIT,
+};
+use crate::commands::AppState;
+use crate::utils::file_lock::FileLock;
+use crate::utils::logger::{log_info, log_warn};
+
+/// 外部修改合并完成事件
+pub const STORAGE_CHANGED_EVENT: &str = "storage-changed";
+
+/// 后台检查间隔
+const POLL_INTERVAL_SECS: u64 = 2;
+
+/// 上次读写磁盘时的文件状态和内容，作为三方合并的基准
+struct Synced {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+    base: Storage,
+}
+
+static SYNCED: Lazy<Mutex<Option<Synced>>> = Lazy::new(|| Mutex::new(None));
+
+/// 冲突中保留的一方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictSide {
+    Local,
+    Remote,
+}
+
+/// 双方都修改了同一账户
+#[derive(Debug, Serialize)]
+pub struct SyncConflict {
+    pub account: AccountSummary,
+    pub kept: ConflictSide,
+    pub reason: String,
+}
+
+/// 合并报告：added / updated / removed 为来自外部的改动
+#[derive(Debug, Default, Serialize)]
+pub struct SyncReport {
+    pub added: Vec<AccountSummary>,
+    pub updated: Vec<AccountSummary>,
+    pub removed: Vec<AccountSummary>,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+fn content_hash(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 记录刚刚读取或写入磁盘的内容（JSON 后端加载和保存后调用）
+pub fn mark_synced(path: &Path, content: &[u8], storage: &Storage) {
+    let metadata = fs::metadata(path).ok();
+    let synced = Synced {
+        path: path.to_path_buf(),
+        modified: metadata.as_ref().and_then(|m| m.modified().ok()),
+        len: metadata.map(|m| m.len()).unwrap_or(0),
+        hash: content_hash(content),
+        base: storage.clone(),
+    };
+    if let Ok(mut current) = SYNCED.lock() {
+        *current = Some(synced);
+    }
+}
+
+/// 文件内容与上次读写时相同时，返回当时的修订号
+pub fn synced_revision(path: &Path, content: &[u8]) -> Option<u64> {
+    let synced = SYNCED.lock().ok()?;
+    synced.as_ref()
+        .filter(|known| known.path == path && known.hash == content_hash(content))
+        .map(|known| known.base.revision)
+}
+
+/// 是否有该文件的读写记录
+pub fn has_synced(path: &Path) -> bool {
+    SYNCED.lock().ok().is_some_and(|synced| synced.as_ref().is_some_and(|known| known.path == path))
+}
+
+/// 检查存储文件是否被外部修改，是则合并到内存并写回，返回合并报告
+pub fn sync_external_changes(app: &AppHandle, storage: &mut Storage) -> Option<SyncReport> {
+    if storage.locked || storage.load_error.is_some() || storage.secrets_error.is_some() {
+        return None;
+    }
+
+    let mut synced = SYNCED.lock().ok()?;
+    let known = synced.as_mut()?;
+
+    let metadata = fs::metadata(&known.path).ok()?;
+    if metadata.modified().ok() == known.modified && metadata.len() == known.len {
+        return None;
+    }
+    if get_storage_backend() != StorageBackend::Json || get_storage_path(app).ok()? != known.path {
+        return None;
+    }
+
+    let lock = FileLock::shared(&known.path).ok();
+    let content = fs::read(&known.path).ok()?;
+    drop(lock);
+    known.modified = metadata.modified().ok();
+    known.len = metadata.len();
+    // 只有修改时间变化（如同步工具重写了相同内容）
+    if content_hash(&content) == known.hash {
+        return None;
+    }
+
+    // 同步工具写入过程中可能读到不完整的内容，等待下一次检查
+    let remote = match std::str::from_utf8(&content)
+        .map_err(|e| e.to_string())
+        .and_then(|text| Storage::decode_backup(app, text))
+    {
+        Ok(remote) if remote.secrets_error.is_none() => remote,
+        Ok(remote) => {
+            log_warn(format!("Storage file changed externally but its secrets could not be read: {}", remote.secrets_error.unwrap_or_default()));
+            return None;
+        }
+        Err(e) => {
+            log_warn(format!("Storage file changed externally but could not be read: {}", e));
+            return None;
+        }
+    };
+
+    let (mut merged, report) = merge(&known.base, storage, &remote);
+    known.hash = content_hash(&content);
+    known.base = remote.clone();
+    drop(synced);
+
+    // 有本地改动需要保留时写回，写回后会重新记录基准
+    let has_local_changes = serde_json::to_value(&merged).ok() != serde_json::to_value(&remote).ok();
+    if has_local_changes {
+        if let Err(e) = merged.save(app) {
+            log_warn(format!("Failed to save merged storage: {}", e));
+        }
+    }
+
+    log_info(format!(
+        "Storage changed externally: {} added, {} updated, {} removed, {} conflicts",
+        report.added.len(), report.updated.len(), report.removed.len(), report.conflicts.len()
+    ));
+    *storage = merged;
+
+    let _ = app.emit(STORAGE_CHANGED_EVENT, &report);
+    Some(report)
+}
+
+/// 后台定时检查外部修改
+pub async fn run_storage_watcher(app: AppHandle) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(POLL_INTERVAL_SECS)).await;
+
+        let Some(state) = app.try_state::<AppState>() else {
+            continue;
+        };
+        // 直接加锁而不是 state.storage()，后台检查不应刷新空闲计时
+        let Ok(mut storage) = state.storage.lock() else {
+            continue;
+        };
+        sync_external_changes(&app, &mut storage);
+    }
+}
+
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// 比较账户内容，忽略切换账户时改动的 is_active 和 last_used_at
+fn same_content(a: &Account, b: &Account) -> bool {
+    same(&Account { is_active: b.is_active, last_used_at: b.last_used_at, ..a.clone() }, b)
+}
+
+/// 激活状态单独三方合并：取改动过的一方，最近使用时间取较大值
+fn merge_activity(kept: &mut Account, base: Option<&Account>, local: &Account, remote: &Account) {
+    kept.is_active = match base {
+        Some(base) if local.is_active == base.is_active => remote.is_active,
+        _ => local.is_active,
+    };
+    kept.last_used_at = local.last_used_at.max(remote.last_used_at);
+}
+
+/// 三方合并：base 为上次同步时的磁盘内容，local 为内存中的数据，remote 为外部修改后的文件
+pub fn merge(base: &Storage, local: &Storage, remote: &Storage) -> (Storage, SyncReport) {
+    let mut report = SyncReport::default();
+    let find = |accounts: &[Account], id: &str| accounts.iter().find(|a| a.id == id).cloned();
+
+    // 保持外部文件中的顺序，本地新增的账户追加在后
+    let mut ids: Vec<&str> = remote.accounts.iter().map(|a| a.id.as_str()).collect();
+    ids.extend(local.accounts.iter().map(|a| a.id.as_str()).filter(|id| !remote.accounts.iter().any(|a| a.id == *id)));
+
+    let mut accounts = Vec::new();
+    for id in ids {
+        let (b, l, r) = (find(&base.accounts, id), find(&local.accounts, id), find(&remote.accounts, id));
+        let mut kept = match (b.clone(), l.clone(), r.clone()) {
+            (_, Some(l), Some(r)) if same_content(&l, &r) => Some(r),
+            (Some(b), Some(l), Some(r)) if same_content(&l, &b) => {
+                report.updated.push(AccountSummary::from(&r));
+                Some(r)
+            }
+            (Some(b), Some(l), Some(r)) if same_content(&r, &b) => Some(l),
+            (_, Some(l), Some(r)) => {
+                let (kept, side) = if l.updated_at > r.updated_at { (l, ConflictSide::Local) } else { (r, ConflictSide::Remote) };
+                report.conflicts.push(SyncConflict {
+                    account: AccountSummary::from(&kept),
+                    kept: side,
+                    reason: "modified on both sides".to_string(),
+                });
+                if side == ConflictSide::Remote {
+                    report.updated.push(AccountSummary::from(&kept));
+                }
+                Some(kept)
+            }
+            (None, None, Some(r)) => {
+                report.added.push(AccountSummary::from(&r));
+                Some(r)
+            }
+            (None, Some(l), None) => Some(l),
+            (Some(b), Some(l), None) if same_content(&l, &b) => {
+                report.removed.push(AccountSummary::from(&l));
+                None
+            }
+            (Some(_), Some(l), None) => {
+                report.conflicts.push(SyncConflict {
+                    account: AccountSummary::from(&l),
+                    kept: ConflictSide::Local,
+                    reason: "deleted externally but modified locally".to_string(),
+                });
+                Some(l)
+            }
+            (Some(b), None, Some(r)) if same_content(&r, &b) => None,
+            (Some(_), None, Some(r)) => {
+                report.conflicts.push(SyncConflict {
+                    account: AccountSummary::from(&r),
+                    kept: ConflictSide::Remote,
+                    reason: "deleted locally but modified externally".to_string(),
+                });
+                Some(r)
+            }
+            _ => None,
+        };
+        if let (Some(kept), Some(l), Some(r)) = (kept.as_mut(), &l, &r) {
+            merge_activity(kept, b.as_ref(), l, r);
+        }
+        accounts.extend(kept);
+    }
+    keep_single_active(&mut accounts);
+
+    // 修订号取外部文件的，写回时在此基础上递增
+    let mut merged = Storage {
+        version: local.version.max(remote.version),
+        revision: remote.revision,
+        accounts,
+        ..local.clone()
+    };
+
+    merged.machine_id = if same(&local.machine_id, &base.machine_id) { remote.machine_id.clone() } else { local.machine_id.clone() };
+
+    // 机器码绑定：以外部文件为准，再应用本地的改动
+    let mut bindings = remote.account_machine_bindings.clone();
+    for (id, machine_id) in &local.account_machine_bindings {
+        if base.account_machine_bindings.get(id) != Some(machine_id) {
+            bindings.insert(id.clone(), machine_id.clone());
+        }
+    }
+    for id in base.account_machine_bindings.keys() {
+        if !local.account_machine_bindings.contains_key(id) {
+            bindings.remove(id);
+        }
+    }
+    merged.account_machine_bindings = bindings;
+
+    // 回收站：一方清除（或过期自动清除）了基准中已有的条目时不再保留，已恢复的账户不再保留在回收站中
+    let in_trash = |storage: &Storage, id: &str| storage.trash.iter().any(|d| d.account.id == id);
+    let mut trash: Vec<_> = remote.trash.iter()
+        .filter(|d| in_trash(local, &d.account.id) || !in_trash(base, &d.account.id))
+        .cloned()
+        .collect();
+    for deleted in &local.trash {
+        if !in_trash(remote, &deleted.account.id) && !in_trash(base, &deleted.account.id) {
+            trash.push(deleted.clone());
+        }
+    }
+    trash.retain(|d| !merged.accounts.iter().any(|a| a.id == d.account.id));
+    merged.trash = trash;
+
+    let mut history = remote.switch_history.clone();
+    for record in &local.switch_history {
+        if !history.iter().any(|r| same(r, record)) {
+            history.push(record.clone());
+        }
+    }
+    history.sort_by_key(|r| r.switched_at);
+    let excess = history.len().saturating_sub(SWITCH_HISTORY_LIMIT);
+    history.drain(..excess);
+    merged.switch_history = history;
+
+    (merged, report)
+}
+
+/// 两边分别切换了同一平台的账户时，保留最近使用的一个为激活状态
+fn keep_single_active(accounts: &mut [Account]) {
+    let mut latest: HashMap<String, (usize, i64)> = HashMap::new();
+    for (index, account) in accounts.iter().enumerate().filter(|(_, a)| a.is_active) {
+        let entry = latest.entry(account.platform.clone()).or_insert((index, account.last_used_at));
+        if account.last_used_at > entry.1 {
+            *entry = (index, account.last_used_at);
+        }
+    }
+    for (index, account) in accounts.iter_mut().enumerate() {
+        if account.is_active && latest.get(&account.platform).map(|(i, _)| *i) != Some(index) {
+            account.is_active = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::storage::{DeletedAccount, SwitchRecord};
+    use crate::core::test_support::{self, storage};
+
+    fn account(id: &str, email: &str, updated_at: i64) -> Account {
+        Account { updated_at, ..test_support::account(id, "claude", email) }
+    }
+
+    #[test]
+    fn test_merge_non_overlapping_changes() {
+        let base = storage(vec![account("a", "a@x.com", 1), account("b", "b@x.com", 1), account("c", "c@x.com", 1)]);
+        // 本地：修改 a，新增 d
+        let local = storage(vec![account("a", "a2@x.com", 2), account("b", "b@x.com", 1), account("c", "c@x.com", 1), account("d", "d@x.com", 2)]);
+        // 外部：删除 b，修改 c，新增 e
+        let remote = storage(vec![account("a", "a@x.com", 1), account("c", "c2@x.com", 3), account("e", "e@x.com", 3)]);
+
+        let (merged, report) = merge(&base, &local, &remote);
+        let emails: Vec<_> = merged.accounts.iter().map(|a| a.email.as_str()).collect();
+        assert_eq!(emails, vec!["a2@x.com", "c2@x.com", "e@x.com", "d@x.com"]);
+        assert_eq!(report.added.len(), 1);
+        assert_eq!(report.updated.len(), 1);
+        assert_eq!(report.removed.len(), 1);
+        assert!(report.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_merge_conflicts() {
+        let base = storage(vec![account("a", "a@x.com", 1), account("b", "b@x.com", 1)]);
+        let local = storage(vec![account("a", "local@x.com", 5), account("b", "b-local@x.com", 2)]);
+        let remote = storage(vec![account("a", "remote@x.com", 3)]);
+
+        let (merged, report) = merge(&base, &local, &remote);
+        // a：本地较新；b：外部删除但本地修改过，保留
+        let emails: Vec<_> = merged.accounts.iter().map(|a| a.email.as_str()).collect();
+        assert_eq!(emails, vec!["local@x.com", "b-local@x.com"]);
+        assert_eq!(report.conflicts.len(), 2);
+        assert!(report.conflicts.iter().all(|c| c.kept == ConflictSide::Local));
+    }
+
+    #[test]
+    fn test_merge_trash_and_history() {
+        let deleted = |id: &str| DeletedAccount { account: account(id, "t@x.com", 1), deleted_at: 1 };
+        let record = |at: i64| SwitchRecord { platform: "claude".to_string(), account_id: "a".to_string(), previous_id: None, switched_at: at };
+
+        // 基准中有 x、y；本地清除了 x，外部清除了 y；两边各自新删除了 l、r
+        let base = Storage { trash: vec![deleted("x"), deleted("y")], ..Storage::new() };
+        let local = Storage { trash: vec![deleted("y"), deleted("l")], switch_history: (0..150).map(record).collect(), ..Storage::new() };
+        let remote = Storage { trash: vec![deleted("x"), deleted("r")], switch_history: (100..300).map(record).collect(), ..Storage::new() };
+
+        let (merged, _) = merge(&base, &local, &remote);
+        let trash: Vec<_> = merged.trash.iter().map(|d| d.account.id.as_str()).collect();
+        assert_eq!(trash, vec!["r", "l"]);
+        assert_eq!(merged.switch_history.len(), SWITCH_HISTORY_LIMIT);
+        assert_eq!(merged.switch_history.last().unwrap().switched_at, 299);
+    }
+
+    #[test]
+    fn test_switch_does_not_conflict_with_edit() {
+        let mut a = account("a", "a@x.com", 1);
+        a.is_active = true;
+        let base = storage(vec![a.clone(), account("b", "b@x.com", 1)]);
+
+        // 本地切换到 b，外部修改了 a
+        let mut local = base.clone();
+        local.activate("b").unwrap();
+        let remote = storage(vec![Account { email: "a2@x.com".to_string(), updated_at: 5, ..a }, account("b", "b@x.com", 1)]);
+
+        let (merged, report) = merge(&base, &local, &remote);
+        assert!(report.conflicts.is_empty());
+        assert_eq!(merged.accounts[0].email, "a2@x.com");
+        assert!(!merged.accounts[0].is_active);
+        assert!(merged.accounts[1].is_active);
+        assert!(merged.accounts[1].last_used_at > 0);
+    }
+
+    #[test]
+    fn test_single_active_after_merge() {
+        let mut a = account("a", "a@x.com", 1);
+        let mut b = account("b", "b@x.com", 1);
+        let base = storage(vec![a.clone(), b.clone()]);
+        a.is_active = true;
+        a.last_used_at = 10;
+        let local = storage(vec![a.clone(), account("b", "b@x.com", 1)]);
+        b.is_active = true;
+        b.last_used_at = 20;
+        let remote = storage(vec![account("a", "a@x.com", 1), b]);
+
+        let (merged, _) = merge(&base, &local, &remote);
+        let active: Vec<_> = merged.accounts.iter().filter(|a| a.is_active).map(|a| a.id.as_str()).collect();
+        assert_eq!(active, vec!["b"]);
+    }
+}
@@ -4,12 +4,107 @@ mod utils;
 
 use commands::*;
 use core::Storage;
-use std::sync::Mutex;
 use tauri::{Manager, Emitter, menu::{Menu, MenuItem}, tray::TrayIconBuilder};
-use utils::logger::{log_info, log_error};
+use utils::logger::log_info;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let commands: fn(tauri::ipc::Invoke) -> bool = tauri::generate_handler![
+        get_accounts,
+        query_accounts,
+        add_account,
+        update_account,
+        delete_account,
+        export_accounts,
+        import_accounts,
+        get_log_file_path,
+        query_logs,
+        tail_logs,
+        set_log_level,
+        core::settings::get_settings,
+        core::settings::update_settings,
+        core::relocate::set_storage_path,
+        core::storage::get_current_storage_path,
+        core::storage::select_storage_directory,
+        core::storage::get_current_storage_backend,
+        core::storage::set_storage_backend,
+        core::vault_registry::list_vaults,
+        core::vault_registry::create_vault,
+        core::vault_registry::open_vault,
+        core::vault_registry::close_vault,
+        core::recovery::get_storage_recovery_report,
+        core::audit::get_audit_log,
+        core::consistency::check_storage,
+        core::consistency::repair_storage,
+        commands::switching::get_switch_history,
+        commands::switching::switch_back,
+        core::secret_store::migrate_secrets,
+        core::snapshot::list_snapshots,
+        core::snapshot::diff_snapshot,
+        core::snapshot::restore_snapshot,
+        core::snapshot::get_snapshot_settings,
+        core::snapshot::set_snapshot_settings,
+        commands::trash::list_deleted_accounts,
+        commands::trash::restore_account,
+        commands::trash::purge_trash,
+        commands::trash::get_trash_retention,
+        commands::trash::set_trash_retention,
+        import::import_from_db,
+        machine::get_machine_id,
+        machine::set_machine_id,
+        machine::bind_machine_id,
+        machine::unbind_machine_id,
+        machine::get_machine_id_for_account,
+        machine::get_all_machine_id_bindings,
+        // Vault 命令
+        vault::get_vault_status,
+        vault::unlock_vault,
+        vault::enable_vault_encryption,
+        vault::disable_vault_encryption,
+        vault::change_vault_passphrase,
+        vault::lock_vault,
+        vault::get_auto_lock_minutes,
+        vault::set_auto_lock_minutes,
+        // Antigravity 命令
+        antigravity::antigravity_prepare_oauth_url,
+        antigravity::antigravity_complete_oauth,
+        antigravity::antigravity_add_by_token,
+        antigravity::antigravity_refresh_token,
+        antigravity::antigravity_get_quota,
+        antigravity::antigravity_scan_databases,
+        antigravity::select_db_file,
+        antigravity::antigravity_switch_account,
+        // Kiro 命令
+        kiro::kiro_start_device_auth,
+        kiro::kiro_poll_token,
+        kiro::kiro_check_quota,
+        kiro::kiro_refresh_token,
+        kiro::kiro_cancel_auth,
+        kiro::kiro_import_sso_token,
+        kiro::kiro_verify_credentials,
+        kiro::kiro_social_login,
+        kiro::switch_kiro_account,
+        kiro::open_url_in_private_mode,
+        // Claude 命令
+        claude::switch_claude_account,
+        claude::get_claude_config,
+        claude::verify_claude_api_key,
+        // Codex 命令
+        codex::switch_codex_account,
+        codex::get_codex_config,
+        // Gemini 命令
+        gemini::switch_gemini_account,
+        gemini::get_gemini_config,
+        // Provider 命令
+        commands::provider::get_claude_provider_config,
+        commands::provider::apply_claude_provider,
+        commands::provider::get_codex_provider_config,
+        commands::provider::apply_codex_provider,
+        commands::provider::get_gemini_provider_config,
+        commands::provider::apply_gemini_provider,
+        commands::provider::get_platform_versions,
+    ];
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
@@ -41,9 +136,16 @@ pub fn run() {
                 eprintln!("Failed to initialize logger: {}", e);
             }
             
+            if let Err(e) = core::settings::init(app.handle()) {
+                eprintln!("Failed to initialize settings: {}", e);
+            }
+            
             app.manage(core::StorageConfig {
                 custom_path: std::sync::Mutex::new(None),
             });
+            let settings = core::settings::load();
+            utils::logger::set_log_rotation(settings.log_rotation);
+            app.manage(core::vault::VaultState::new(settings.auto_lock_minutes));
             app.manage(core::kiro::DeepLinkState {
                 sender: std::sync::Mutex::new(None),
             });
@@ -70,25 +172,23 @@ pub fn run() {
             }
 
             // Initialize storage
-            let storage = Storage::load(&app.handle())
-                .unwrap_or_else(|e| {
-                    log_error(&format!("Failed to load storage: {}", e));
-                    Storage::new()
-                });
+            let storage = Storage::load_session(app.handle());
             
-            app.manage(AppState {
-                storage: Mutex::new(storage),
-            });
+            app.manage(AppState::new(app.handle().clone(), storage));
+            tauri::async_runtime::spawn(vault::run_idle_lock_timer(app.handle().clone()));
+            tauri::async_runtime::spawn(core::watcher::run_storage_watcher(app.handle().clone()));
 
             // =============================
             // Setup System Tray
             // =============================
             let show_item = MenuItem::with_id(app, "show", "显示主窗口", true, None::<&str>)?;
+            let lock_item = MenuItem::with_id(app, "lock", "锁定", true, None::<&str>)?;
             let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_item, &quit_item])?;
+            let menu = Menu::with_items(app, &[&show_item, &lock_item, &quit_item])?;
 
             // Save menu item IDs
             let show_id = show_item.id().0.clone();
+            let lock_id = lock_item.id().0.clone();
             let quit_id = quit_item.id().0.clone();
 
             // Load tray icon from app icon
@@ -109,6 +209,11 @@ pub fn run() {
                                 log_info("Window shown from tray menu");
                             }
                         }
+                        id if id == lock_id => {
+                            if let Err(e) = vault::lock_now(app) {
+                                log_info(format!("Lock from tray menu skipped: {}", e));
+                            }
+                        }
                         id if id == quit_id => {
                             log_info("Quit from tray menu");
                             app.exit(0);
@@ -142,63 +247,11 @@ pub fn run() {
             
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
-            get_accounts,
-            add_account,
-            update_account,
-            delete_account,
-            export_accounts,
-            import_accounts,
-            get_log_file_path,
-            core::storage::set_storage_path,
-            core::storage::get_current_storage_path,
-            core::storage::select_storage_directory,
-            import::import_from_db,
-            machine::get_machine_id,
-            machine::set_machine_id,
-            machine::bind_machine_id,
-            machine::unbind_machine_id,
-            machine::get_machine_id_for_account,
-            machine::get_all_machine_id_bindings,
-            // Antigravity 命令
-            antigravity::antigravity_prepare_oauth_url,
-            antigravity::antigravity_complete_oauth,
-            antigravity::antigravity_add_by_token,
-            antigravity::antigravity_refresh_token,
-            antigravity::antigravity_get_quota,
-            antigravity::antigravity_scan_databases,
-            antigravity::select_db_file,
-            antigravity::antigravity_switch_account,
-            // Kiro 命令
-            kiro::kiro_start_device_auth,
-            kiro::kiro_poll_token,
-            kiro::kiro_check_quota,
-            kiro::kiro_refresh_token,
-            kiro::kiro_cancel_auth,
-            kiro::kiro_import_sso_token,
-            kiro::kiro_verify_credentials,
-            kiro::kiro_social_login,
-            kiro::switch_kiro_account,
-            kiro::open_url_in_private_mode,
-            // Claude 命令
-            claude::switch_claude_account,
-            claude::get_claude_config,
-            claude::verify_claude_api_key,
-            // Codex 命令
-            codex::switch_codex_account,
-            codex::get_codex_config,
-            // Gemini 命令
-            gemini::switch_gemini_account,
-            gemini::get_gemini_config,
-            // Provider 命令
-            commands::provider::get_claude_provider_config,
-            commands::provider::apply_claude_provider,
-            commands::provider::get_codex_provider_config,
-            commands::provider::apply_codex_provider,
-            commands::provider::get_gemini_provider_config,
-            commands::provider::apply_gemini_provider,
-            commands::provider::get_platform_versions,
-        ])
+        .invoke_handler(move |invoke| {
+            // 同步命令在此 span 内执行；异步命令交给异步运行时执行，各自用 #[instrument] 创建以命令名命名的 span
+            let _span = utils::logger::command_span(&invoke).entered();
+            commands(invoke)
+        })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,79 @@
+//! 原子文件写入
+//!
+//! 先写入同目录下的临时文件并 fsync，再 rename 覆盖目标文件。
+//! 进程崩溃或断电时，目标文件要么是旧内容，要么是完整的新内容。
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 原子写入文件
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    let tmp_path = temp_path_for(path)?;
+
+    let result = (|| {
+        let mut file = File::create(&tmp_path)
+            .map_err(|e| format!("Failed to create temp file: {}", e))?;
+        file.write_all(content)
+            .map_err(|e| format!("Failed to write temp file: {}", e))?;
+        file.sync_all()
+            .map_err(|e| format!("Failed to sync temp file: {}", e))?;
+        drop(file);
+
+        fs::rename(&tmp_path, path)
+            .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+        return result;
+    }
+
+    sync_parent_dir(path);
+    Ok(())
+}
+
+/// 临时文件放在目标文件同目录，保证 rename 不跨文件系统
+fn temp_path_for(path: &Path) -> Result<PathBuf, String> {
+    let file_name = path.file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("Invalid file path: {}", path.display()))?;
+
+    Ok(path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id())))
+}
+
+/// 同步父目录，确保 rename 本身已落盘（仅 Unix 支持对目录 fsync）
+fn sync_parent_dir(path: &Path) {
+    #[cfg(unix)]
+    {
+        if let Some(parent) = path.parent() {
+            if let Ok(dir) = File::open(parent) {
+                let _ = dir.sync_all();
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_replaces_content() {
+        let dir = crate::core::test_support::temp_dir("atomic");
+        let path = dir.join("accounts.json");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        // 不应残留临时文件
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
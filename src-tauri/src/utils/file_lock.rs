@@ -0,0 +1,121 @@
+//! 跨进程文件锁
+//!
+//! 在应用数据目录的 `locks/` 下为目标文件创建 `<文件名>-<路径哈希>.lock` 并加建议锁（advisory lock），
+//! 只约束同样遵守该约定的进程（如同一用户运行的另一个实例或命令行工具）。
+//! 数据文件本身通过 rename 原子替换，不能直接对它加锁；锁文件也不放在数据文件旁，
+//! 避免存储目录被同步盘同步时把锁文件同步到其他设备。
+
+use sha1::{Digest, Sha1};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 等待其他进程释放锁的最长时间
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const RETRY_INTERVAL: Duration = Duration::from_millis(20);
+
+/// 持有期间保持加锁，drop 时关闭文件并释放锁
+pub struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    /// 独占锁：写入时使用
+    pub fn exclusive(path: &Path) -> Result<Self, String> {
+        Self::acquire(path, true, LOCK_TIMEOUT)
+    }
+
+    /// 共享锁：读取时使用，可与其他读取者同时持有
+    pub fn shared(path: &Path) -> Result<Self, String> {
+        Self::acquire(path, false, LOCK_TIMEOUT)
+    }
+
+    fn acquire(path: &Path, exclusive: bool, timeout: Duration) -> Result<Self, String> {
+        Self::lock(&lock_path_for(path)?, exclusive, timeout)
+    }
+
+    fn lock(lock_path: &Path, exclusive: bool, timeout: Duration) -> Result<Self, String> {
+        if let Some(dir) = lock_path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create lock directory {}: {}", dir.display(), e))?;
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(lock_path)
+            .map_err(|e| format!("Failed to open lock file {}: {}", lock_path.display(), e))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let result = if exclusive { file.try_lock() } else { file.try_lock_shared() };
+            match result {
+                Ok(()) => return Ok(Self { _file: file }),
+                Err(TryLockError::WouldBlock) if Instant::now() < deadline => thread::sleep(RETRY_INTERVAL),
+                Err(TryLockError::WouldBlock) => {
+                    return Err(format!("Timed out waiting for another process to release {}", lock_path.display()));
+                }
+                Err(TryLockError::Error(e)) => {
+                    return Err(format!("Failed to lock {}: {}", lock_path.display(), e));
+                }
+            }
+        }
+    }
+}
+
+fn lock_path_for(path: &Path) -> Result<PathBuf, String> {
+    let dir = dirs::data_dir()
+        .ok_or_else(|| "Failed to get app data directory".to_string())?
+        .join("com.nexus.account-manager")
+        .join("locks");
+    lock_path_in(&dir, path)
+}
+
+/// dir 下 path 对应的锁文件。所在目录存在时按规范化后的路径计算哈希，同一文件的不同写法共用一把锁
+fn lock_path_in(dir: &Path, path: &Path) -> Result<PathBuf, String> {
+    let file_name = path.file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("Invalid file path: {}", path.display()))?;
+    let path = path.parent()
+        .and_then(|parent| parent.canonicalize().ok())
+        .map(|parent| parent.join(file_name))
+        .unwrap_or_else(|| path.to_path_buf());
+    let digest = Sha1::digest(path.to_string_lossy().as_bytes());
+    let hash: String = digest.iter().take(8).map(|b| format!("{:02x}", b)).collect();
+    Ok(dir.join(format!("{}-{}.lock", file_name, hash)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::temp_dir;
+
+    #[test]
+    fn test_exclusive_and_shared() {
+        let dir = temp_dir("lock");
+        let path = dir.join("accounts.json");
+        let short = Duration::from_millis(50);
+
+        // 锁文件在锁目录下，同一文件的不同写法得到同一把锁，不同文件互不影响
+        let locks = dir.join("locks");
+        let lock_path = lock_path_in(&locks, &path).unwrap();
+        assert!(lock_path.starts_with(&locks));
+        assert_eq!(lock_path_in(&locks, &dir.join(".").join("accounts.json")).unwrap(), lock_path);
+        assert_ne!(lock_path_in(&locks, &dir.join("other.json")).unwrap(), lock_path);
+
+        let first = FileLock::lock(&lock_path, false, short).unwrap();
+        let second = FileLock::lock(&lock_path, false, short).unwrap();
+        assert!(FileLock::lock(&lock_path, true, short).is_err());
+
+        drop((first, second));
+        let writer = FileLock::lock(&lock_path, true, short).unwrap();
+        assert!(FileLock::lock(&lock_path, false, short).is_err());
+
+        drop(writer);
+        assert!(FileLock::lock(&lock_path, true, short).is_ok());
+        assert!(!dir.join("accounts.json.lock").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
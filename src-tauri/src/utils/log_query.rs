@@ -0,0 +1,349 @@
+//! 日志查看
+//!
+//! 解析 app.log 的 JSON 行（见 utils::logger），在当前日志和轮转后的历史日志中按级别、
+//! 时间范围、模块和文本筛选；开启跟随后，新写入的日志通过 `log-line` 事件实时推送给前端。
+
+use chrono::{DateTime, FixedOffset};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::mpsc;
+use tauri::{AppHandle, Emitter};
+use tracing::Level;
+
+use super::logger::{self, log_warn};
+
+/// 新日志事件，携带一条 LogEntry
+pub const LOG_LINE_EVENT: &str = "log-line";
+
+/// 默认最多返回的日志条数
+const DEFAULT_LIMIT: usize = 1000;
+
+/// 倒序读取时每次读入的字节数
+const CHUNK_SIZE: u64 = 64 * 1024;
+
+/// 一条日志
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    pub timestamp: DateTime<FixedOffset>,
+    pub level: String,
+    /// 本应用的模块（如 `core::storage`），其他 crate 的日志为其 target
+    pub module: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+}
+
+impl LogEntry {
+    /// 解析日志文件中的一行，无法解析的行（如旧版本的纯文本日志）返回 None
+    pub fn parse(line: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(line).ok()?;
+        let text = |v: &Value, key: &str| v.get(key).and_then(Value::as_str).map(str::to_string);
+        let span = value.get("span").unwrap_or(&Value::Null);
+        // 同步命令的 span 名为 command，命令名在 command 字段中；异步命令的 span 名即命令名
+        let command = text(span, "command")
+            .or_else(|| text(span, "name").filter(|name| name != "command"));
+
+        Some(Self {
+            timestamp: DateTime::parse_from_rfc3339(value.get("timestamp")?.as_str()?).ok()?,
+            level: text(&value, "level")?,
+            module: text(&value, "module").or_else(|| text(&value, "target")).unwrap_or_default(),
+            message: text(&value, "message").unwrap_or_default(),
+            command,
+            platform: text(span, "platform"),
+            account_id: text(span, "account_id"),
+        })
+    }
+}
+
+/// 查询条件，未设置的条件不参与筛选
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogQuery {
+    /// 最低级别，如 `warn` 返回 WARN 和 ERROR
+    pub level: Option<String>,
+    pub since: Option<DateTime<FixedOffset>>,
+    pub until: Option<DateTime<FixedOffset>>,
+    /// 模块及其子模块，如 `core` 包含 `core::storage`
+    pub module: Option<String>,
+    /// 消息子串，忽略大小写
+    pub text: Option<String>,
+    /// 最多返回的条数（最新的若干条），默认 1000
+    pub limit: Option<usize>,
+}
+
+impl LogQuery {
+    fn matches(&self, entry: &LogEntry, level: Option<Level>, text: Option<&str>) -> bool {
+        if let Some(level) = level {
+            // tracing 中越详细的级别越大
+            if !entry.level.parse::<Level>().is_ok_and(|l| l <= level) {
+                return false;
+            }
+        }
+        if self.since.is_some_and(|since| entry.timestamp < since)
+            || self.until.is_some_and(|until| entry.timestamp > until)
+        {
+            return false;
+        }
+        if let Some(module) = &self.module {
+            let module = module.strip_prefix(concat!(env!("CARGO_CRATE_NAME"), "::")).unwrap_or(module);
+            let matched = entry.module.strip_prefix(module)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"));
+            if !matched {
+                return false;
+            }
+        }
+        text.is_none_or(|text| entry.message.to_lowercase().contains(text))
+    }
+
+    /// 从最新的日志开始查找，返回按时间先后排列的结果。path 为当前日志文件
+    pub fn run(&self, path: &Path) -> Result<Vec<LogEntry>, String> {
+        let level = self.level.as_deref()
+            .map(|l| l.parse::<Level>().map_err(|_| format!("Invalid log level '{}'", l)))
+            .transpose()?;
+        let text = self.text.as_ref().map(|t| t.to_lowercase());
+        let limit = self.limit.unwrap_or(DEFAULT_LIMIT);
+
+        let mut files = vec![path.to_path_buf()];
+        files.extend(logger::log_generations(path).into_iter().map(|(_, p)| p));
+
+        // 从新到旧收集
+        let mut entries = Vec::new();
+        for file in files {
+            let remaining = limit - entries.len();
+            if remaining == 0 {
+                break;
+            }
+            let scanned = if file.extension().is_some_and(|e| e == "gz") {
+                self.scan_forward(&file, remaining, level, text.as_deref())
+            } else {
+                self.scan_backward(&file, remaining, level, text.as_deref())
+            };
+            match scanned {
+                Ok((found, reached_since)) => {
+                    entries.extend(found);
+                    // 更早的文件只会更旧
+                    if reached_since {
+                        break;
+                    }
+                }
+                // 日志在查询期间可能刚好被轮转
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => log_warn(format!("Failed to read {}: {}", file.display(), e)),
+            }
+        }
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// 从文件末尾向前查找最多 limit 条，返回结果（从新到旧）及是否已读到 since 之前
+    fn scan_backward(&self, path: &Path, limit: usize, level: Option<Level>, text: Option<&str>) -> io::Result<(Vec<LogEntry>, bool)> {
+        let mut found = Vec::new();
+        for line in ReverseLines::open(path)? {
+            let Some(entry) = LogEntry::parse(&line?) else {
+                continue;
+            };
+            if self.since.is_some_and(|since| entry.timestamp < since) {
+                return Ok((found, true));
+            }
+            if self.matches(&entry, level, text) {
+                found.push(entry);
+                if found.len() == limit {
+                    break;
+                }
+            }
+        }
+        Ok((found, false))
+    }
+
+    /// 压缩文件无法倒序读取：顺序解压，只保留最新的 limit 条匹配结果
+    fn scan_forward(&self, path: &Path, limit: usize, level: Option<Level>, text: Option<&str>) -> io::Result<(Vec<LogEntry>, bool)> {
+        let mut found = VecDeque::with_capacity(limit);
+        let mut reached_since = false;
+        for line in BufReader::new(GzDecoder::new(File::open(path)?)).lines() {
+            let Some(entry) = LogEntry::parse(&line?) else {
+                continue;
+            };
+            if self.since.is_some_and(|since| entry.timestamp < since) {
+                reached_since = true;
+                continue;
+            }
+            if self.matches(&entry, level, text) {
+                if found.len() == limit {
+                    found.pop_front();
+                }
+                found.push_back(entry);
+            }
+        }
+        Ok((found.into_iter().rev().collect(), reached_since))
+    }
+}
+
+/// 从文件末尾向前逐行读取，不把整个文件读入内存
+struct ReverseLines {
+    file: File,
+    /// 尚未读入的部分的长度
+    pos: u64,
+    /// 已读入但尚未返回的内容（位于 pos 之后）
+    buf: Vec<u8>,
+}
+
+impl ReverseLines {
+    fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let pos = file.metadata()?.len();
+        Ok(Self { file, pos, buf: Vec::new() })
+    }
+}
+
+impl Iterator for ReverseLines {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(i) = self.buf.iter().rposition(|&b| b == b'\n') {
+                let line = self.buf.split_off(i + 1);
+                self.buf.truncate(i);
+                if line.is_empty() {
+                    continue;
+                }
+                return Some(Ok(String::from_utf8_lossy(&line).into_owned()));
+            }
+            if self.pos == 0 {
+                if self.buf.is_empty() {
+                    return None;
+                }
+                let line = std::mem::take(&mut self.buf);
+                return Some(Ok(String::from_utf8_lossy(&line).into_owned()));
+            }
+
+            let size = CHUNK_SIZE.min(self.pos);
+            self.pos -= size;
+            let mut chunk = vec![0; size as usize];
+            if let Err(e) = self.file.seek(SeekFrom::Start(self.pos)).and_then(|_| self.file.read_exact(&mut chunk)) {
+                self.pos = 0;
+                self.buf.clear();
+                return Some(Err(e));
+            }
+            chunk.append(&mut self.buf);
+            self.buf = chunk;
+        }
+    }
+}
+
+/// 查询当前日志及历史日志。读取和解压可能较慢，在阻塞线程池中执行，不占用主线程
+pub async fn query(query: LogQuery) -> Result<Vec<LogEntry>, String> {
+    let path = logger::get_log_file_path().ok_or("Log file not initialized")?;
+    tauri::async_runtime::spawn_blocking(move || query.run(&path))
+        .await
+        .map_err(|e| format!("Log query failed: {}", e))?
+}
+
+/// 开始或停止向前端推送新日志
+pub fn follow(app: Option<AppHandle>) {
+    let Some(app) = app else {
+        logger::set_tail(None);
+        return;
+    };
+    let (sender, receiver) = mpsc::channel::<String>();
+    // 替换发送端后，上一个推送线程的接收端断开并退出
+    logger::set_tail(Some(sender));
+    std::thread::spawn(move || {
+        for chunk in receiver {
+            for entry in chunk.lines().filter_map(LogEntry::parse) {
+                let _ = app.emit(LOG_LINE_EVENT, &entry);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{temp_dir, temp_path};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::fs;
+    use std::io::Write;
+
+    fn line(timestamp: &str, level: &str, module: &str, message: &str) -> String {
+        format!(
+            r#"{{"timestamp":"{}","level":"{}","message":"{}","module":"{}","target":"applog","span":{{"command":"switch_back","platform":"codex","name":"command"}}}}"#,
+            timestamp, level, message, module
+        )
+    }
+
+    #[test]
+    fn test_parse_entry() {
+        let entry = LogEntry::parse(&line("2026-01-01T10:00:00+08:00", "INFO", "core::storage", "saved")).unwrap();
+        assert_eq!(entry.module, "core::storage");
+        assert_eq!(entry.platform.as_deref(), Some("codex"));
+        assert_eq!(entry.account_id, None);
+
+        assert_eq!(entry.command.as_deref(), Some("switch_back"));
+        let async_command = r#"{"timestamp":"2026-01-01T10:00:00+08:00","level":"INFO","message":"ok","span":{"name":"kiro_refresh_token"}}"#;
+        assert_eq!(LogEntry::parse(async_command).unwrap().command.as_deref(), Some("kiro_refresh_token"));
+
+        let foreign = LogEntry::parse(r#"{"timestamp":"2026-01-01T10:00:00+08:00","level":"WARN","message":"retry","target":"reqwest::connect"}"#).unwrap();
+        assert_eq!(foreign.module, "reqwest::connect");
+        assert!(LogEntry::parse("[2026-01-01 10:00:00] [INFO] plain text").is_none());
+    }
+
+    #[test]
+    fn test_reverse_lines() {
+        let path = temp_path("reverse", ".log");
+        // 跨越多个读取块，且末尾没有换行
+        let lines: Vec<String> = (0..20000).map(|i| format!("line {}", i)).collect();
+        fs::write(&path, lines.join("\n")).unwrap();
+
+        let read: Vec<String> = ReverseLines::open(&path).unwrap().map(Result::unwrap).collect();
+        assert_eq!(read.len(), lines.len());
+        assert!(read.iter().rev().eq(lines.iter()));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_query_across_generations() {
+        let dir = temp_dir("log-query");
+        let path = dir.join("app.log");
+
+        let old = [
+            line("2026-01-01T10:00:00+00:00", "ERROR", "core::storage", "Disk full"),
+            line("2026-01-01T11:00:00+00:00", "INFO", "core::vault", "unlocked"),
+        ];
+        let mut encoder = GzEncoder::new(File::create(dir.join("app.log.1.gz")).unwrap(), Compression::default());
+        encoder.write_all(old.join("\n").as_bytes()).unwrap();
+        encoder.finish().unwrap();
+        let current = [
+            line("2026-01-02T10:00:00+00:00", "WARN", "core::storage_x", "disk slow"),
+            line("2026-01-02T11:00:00+00:00", "DEBUG", "core::storage", "disk check"),
+        ];
+        fs::write(&path, current.join("\n") + "\n").unwrap();
+
+        let query = LogQuery { module: Some("core::storage".into()), text: Some("DISK".into()), ..LogQuery::default() };
+        let messages = |query: &LogQuery| -> Vec<String> {
+            query.run(&path).unwrap().into_iter().map(|e| e.message).collect()
+        };
+        assert_eq!(messages(&query), vec!["Disk full", "disk check"]);
+        assert_eq!(messages(&LogQuery { level: Some("warn".into()), ..LogQuery::default() }), vec!["Disk full", "disk slow"]);
+        assert_eq!(messages(&LogQuery { limit: Some(1), ..LogQuery::default() }), vec!["disk check"]);
+        assert_eq!(messages(&LogQuery { limit: Some(3), ..LogQuery::default() }), vec!["unlocked", "disk slow", "disk check"]);
+
+        let since = DateTime::parse_from_rfc3339("2026-01-01T10:30:00+00:00").unwrap();
+        let until = DateTime::parse_from_rfc3339("2026-01-02T10:30:00+00:00").unwrap();
+        let range = LogQuery { since: Some(since), until: Some(until), ..LogQuery::default() };
+        assert_eq!(messages(&range), vec!["unlocked", "disk slow"]);
+        assert!(LogQuery { level: Some("loud".into()), ..LogQuery::default() }.run(&path).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,71 +1,335 @@
 //! 统一日志系统
-//! 
-//! 提供统一的日志输出接口，同时输出到控制台和文件
+//!
+//! 基于 tracing：控制台输出可读格式，日志文件 app.log 每行一个 JSON 对象。
+//! 所有输出都经过脱敏（见 utils::redact::redact_text），Token、API Key 等不会以明文写入日志。
+//!
+//! app.log 按大小和日期轮转，保留若干份历史日志（app.log.1 最新），可选 gzip 压缩，
+//! 由设置中的 log_rotation 配置（见 LogRotation）。
+//!
+//! 日志级别按模块过滤（如 `core::storage=debug`），可通过 set_log_level 在运行时修改。
+//! 同步命令在名为 `command` 的 span 中执行，异步命令在以命令名命名的 span 中执行，
+//! span 记录命令名、平台和账户 id，命令执行期间的每条日志都带有这些字段。
 
+use std::collections::BTreeMap;
 use std::fmt::Display;
-use std::fs::{self, OpenOptions};
-use std::io::Write;
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::panic::Location;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::sync::{Mutex, MutexGuard, OnceLock, RwLock};
 use once_cell::sync::Lazy;
-use chrono::Local;
+use chrono::{DateTime, Local, NaiveDate};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::field::Empty;
+use tracing::level_filters::LevelFilter;
+use tracing::{Level, Span};
+use tracing_subscriber::fmt::{self, time::ChronoLocal, MakeWriter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Registry};
 
-/// 日志级别
-#[allow(dead_code)]
-pub enum LogLevel {
-    Info,
-    Warn,
-    Error,
-    Debug,
-}
+use super::redact::redact_text;
 
-/// ANSI 颜色代码
-struct Colors;
+/// 本 crate 的 tracing target 前缀
+const CRATE_TARGET: &str = env!("CARGO_CRATE_NAME");
 
-impl Colors {
-    const RESET: &'static str = "\x1b[0m";
-    
-    // 日志级别颜色
-    const INFO: &'static str = "\x1b[36m";      // 青色 (Cyan)
-    const WARN: &'static str = "\x1b[33m";      // 黄色 (Yellow)
-    const ERROR: &'static str = "\x1b[31m";     // 红色 (Red)
-    const DEBUG: &'static str = "\x1b[35m";     // 紫色 (Magenta)
-    
-    // 时间戳颜色
-    const TIMESTAMP: &'static str = "\x1b[90m"; // 灰色 (Bright Black)
-}
+/// log_info 等函数产生的事件和命令 span 使用的 target；事件已按调用处的模块过滤过，不再经过 EnvFilter。
+/// 异步命令使用 `#[tracing::instrument(target = "applog", skip_all)]`，span 名即命令名（见 utils::log_query）
+const LOG_TARGET: &str = "applog";
 
-/// 最大日志消息长度（字符数）
-const MAX_LOG_LENGTH: usize = 500;
+/// 本 crate 的顶层模块，set_log_level 可省略 crate 名
+const CRATE_MODULES: &[&str] = &["core", "commands", "utils"];
 
-/// 最大日志文件大小（字节）- 10MB
-const MAX_LOG_FILE_SIZE: u64 = 10 * 1024 * 1024;
+/// 历史日志份数上限
+pub const MAX_LOG_FILES: usize = 100;
 
 /// 全局日志文件路径
 static LOG_FILE_PATH: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
 
+/// 当前的日志级别设置
+static LEVELS: Lazy<RwLock<LogLevels>> = Lazy::new(|| RwLock::new(LogLevels::default()));
+
+/// 当前的日志轮转设置
+static ROTATION: Lazy<RwLock<LogRotation>> = Lazy::new(|| RwLock::new(LogRotation::default()));
+
+/// 日志轮转设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogRotation {
+    /// 单个日志文件的大小上限（MB），0 表示不按大小轮转
+    pub max_size_mb: u64,
+    /// 跨天后写入第一条日志时轮转
+    pub daily: bool,
+    /// 保留的历史日志份数，0 表示轮转时直接删除
+    pub max_files: usize,
+    /// 用 gzip 压缩历史日志
+    pub compress: bool,
+}
+
+impl Default for LogRotation {
+    fn default() -> Self {
+        Self {
+            max_size_mb: 10,
+            daily: true,
+            max_files: 7,
+            compress: true,
+        }
+    }
+}
+
+/// 跟随日志时接收新写入内容的发送端，见 utils::log_query::follow
+static TAIL: Lazy<Mutex<Option<Sender<String>>>> = Lazy::new(|| Mutex::new(None));
+
+/// 修改过滤规则的句柄，init_logger 之后可用
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// 日志级别：默认级别和按模块（tracing target 前缀）覆盖的级别
+#[derive(Debug, Clone)]
+struct LogLevels {
+    default: LevelFilter,
+    modules: BTreeMap<String, LevelFilter>,
+}
+
+impl Default for LogLevels {
+    fn default() -> Self {
+        Self {
+            default: LevelFilter::INFO,
+            modules: BTreeMap::new(),
+        }
+    }
+}
+
+impl LogLevels {
+    /// 模块生效的级别：取匹配的最长前缀
+    fn level_for(&self, module: &str) -> LevelFilter {
+        self.modules.iter()
+            .filter(|(prefix, _)| module == prefix.as_str() || module.starts_with(&format!("{}::", prefix)))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+
+    /// 直接使用 tracing 宏的事件（如命令 span）按同样的规则过滤
+    fn env_filter(&self) -> Result<EnvFilter, String> {
+        let mut directives = vec![self.default.to_string().to_lowercase(), format!("{}=trace", LOG_TARGET)];
+        directives.extend(self.modules.iter().map(|(module, level)| format!("{}={}", module, level.to_string().to_lowercase())));
+        EnvFilter::try_new(directives.join(",")).map_err(|e| format!("Invalid log level directive: {}", e))
+    }
+
+    fn to_map(&self) -> BTreeMap<String, String> {
+        let mut map = BTreeMap::from([(String::new(), self.default.to_string().to_lowercase())]);
+        map.extend(self.modules.iter().map(|(module, level)| (module.clone(), level.to_string().to_lowercase())));
+        map
+    }
+}
+
+/// 写入前脱敏
+struct Redacted<W>(W);
+
+impl<W: Write> Write for Redacted<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // fmt 层一次写入一整条日志，脱敏不会截断在字段中间
+        let text = String::from_utf8_lossy(buf);
+        self.0.write_all(redact_text(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// 日志文件，写入前按轮转设置检查是否需要轮转
+struct LogFile {
+    path: PathBuf,
+    state: Mutex<LogFileState>,
+}
+
+#[derive(Default)]
+struct LogFileState {
+    file: Option<File>,
+    /// 当前文件开始写入的日期
+    date: Option<NaiveDate>,
+}
+
+struct LogFileWriter<'a>(MutexGuard<'a, LogFileState>);
+
+impl Write for LogFileWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(file) = self.0.file.as_mut() {
+            file.write_all(buf)?;
+        }
+        if let Some(tail) = TAIL.lock().ok().as_ref().and_then(|tail| tail.as_ref()) {
+            let _ = tail.send(String::from_utf8_lossy(buf).into_owned());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.file.as_mut().map_or(Ok(()), |file| file.flush())
+    }
+}
+
+impl LogFile {
+    fn new(path: PathBuf) -> Self {
+        Self { path, state: Mutex::new(LogFileState::default()) }
+    }
+
+    fn rotate_if_needed(&self, state: &mut LogFileState) {
+        let rotation = ROTATION.read().map(|r| r.clone()).unwrap_or_default();
+        let today = Local::now().date_naive();
+        let size = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        let too_large = rotation.max_size_mb > 0 && size > rotation.max_size_mb * 1024 * 1024;
+        let new_day = rotation.daily && size > 0 && state.date.is_some_and(|date| date < today);
+
+        if too_large || new_day {
+            state.file = None;
+            // 这里不能调用 log_*，否则会重入日志文件的锁
+            if let Err(e) = rotate(&self.path, &rotation) {
+                eprintln!("Failed to rotate {}: {}", self.path.display(), e);
+            }
+        }
+        if state.file.is_none() || !self.path.exists() {
+            state.file = OpenOptions::new().create(true).append(true).open(&self.path).ok();
+            // 沿用已有文件时以其修改日期为准，启动时即可轮转前一天的日志
+            state.date = fs::metadata(&self.path)
+                .and_then(|m| m.modified())
+                .map(|t| DateTime::<Local>::from(t).date_naive())
+                .ok()
+                .or(Some(today));
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogFile {
+    type Writer = Redacted<LogFileWriter<'a>>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.rotate_if_needed(&mut state);
+        Redacted(LogFileWriter(state))
+    }
+}
+
+/// 第 n 份历史日志：app.log.n，压缩后为 app.log.n.gz
+fn generation_path(path: &Path, n: usize, compressed: bool) -> PathBuf {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("app.log");
+    let suffix = if compressed { ".gz" } else { "" };
+    path.with_file_name(format!("{}.{}{}", name, n, suffix))
+}
+
+/// 已有的历史日志及其编号，按编号从小到大排序
+pub fn log_generations(path: &Path) -> Vec<(usize, PathBuf)> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name().and_then(|n| n.to_str())) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.", name);
+    let mut generations: Vec<(usize, PathBuf)> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_str()?.to_string();
+            let rest = file_name.strip_prefix(&prefix)?;
+            let n = rest.strip_suffix(".gz").unwrap_or(rest).parse().ok()?;
+            Some((n, entry.path()))
+        })
+        .collect();
+    generations.sort();
+    generations
+}
+
+/// 轮转：历史日志编号依次加一，超出份数的删除，当前日志成为第 1 份（按设置压缩）
+fn rotate(path: &Path, rotation: &LogRotation) -> io::Result<()> {
+    // 从最旧的开始移动，避免覆盖
+    for (n, old) in log_generations(path).into_iter().rev() {
+        if n >= rotation.max_files {
+            fs::remove_file(&old)?;
+        } else {
+            let compressed = old.extension().is_some_and(|e| e == "gz");
+            fs::rename(&old, generation_path(path, n + 1, compressed))?;
+        }
+    }
+    if rotation.max_files == 0 {
+        return fs::remove_file(path);
+    }
+
+    let first = generation_path(path, 1, false);
+    fs::rename(path, &first)?;
+    if rotation.compress {
+        let mut input = File::open(&first)?;
+        let mut encoder = GzEncoder::new(File::create(generation_path(path, 1, true))?, Compression::default());
+        io::copy(&mut input, &mut encoder)?;
+        encoder.finish()?;
+        fs::remove_file(&first)?;
+    }
+    Ok(())
+}
+
+/// 设置跟随日志的发送端，None 表示停止跟随
+pub fn set_tail(sender: Option<Sender<String>>) {
+    if let Ok(mut tail) = TAIL.lock() {
+        *tail = sender;
+    }
+}
+
+/// 应用日志轮转设置，下次写入日志时生效
+pub fn set_log_rotation(rotation: LogRotation) {
+    if let Ok(mut current) = ROTATION.write() {
+        *current = rotation;
+    }
+}
+
 /// 初始化日志系统
 pub fn init_logger() -> Result<(), String> {
     let log_dir = get_log_dir()?;
-    
+
     // 确保日志目录存在
     if !log_dir.exists() {
         fs::create_dir_all(&log_dir)
             .map_err(|e| format!("Failed to create log directory: {}", e))?;
     }
-    
+
     // 日志文件路径
     let log_file = log_dir.join("app.log");
-    
+
     // 保存到全局变量
     if let Ok(mut path) = LOG_FILE_PATH.lock() {
         *path = Some(log_file.clone());
     }
-    
+
+    let levels = LEVELS.read().map_err(|e| e.to_string())?.clone();
+    let (filter, handle) = reload::Layer::new(levels.env_filter()?);
+    let console = fmt::layer()
+        .compact()
+        .with_target(false)
+        .with_timer(ChronoLocal::new("%H:%M:%S".to_string()))
+        .with_writer(|| Redacted(io::stdout()));
+    let file = fmt::layer()
+        .json()
+        .flatten_event(true)
+        .with_current_span(true)
+        .with_span_list(false)
+        .with_ansi(false)
+        .with_timer(ChronoLocal::rfc_3339())
+        .with_writer(LogFile::new(log_file.clone()));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(console)
+        .with(file)
+        .try_init()
+        .map_err(|e| format!("Failed to install log subscriber: {}", e))?;
+    let _ = FILTER.set(handle);
+
     // 写入启动日志
-    write_to_file(&log_file, "INFO", &format!("=== Application started at {} ===", Local::now().format("%Y-%m-%d %H:%M:%S")));
-    write_to_file(&log_file, "INFO", &format!("Log file: {}", log_file.display()));
-    
+    log_info(format!("=== Application started at {} ===", Local::now().format("%Y-%m-%d %H:%M:%S")));
+    log_info(format!("Log file: {}", log_file.display()));
+
     Ok(())
 }
 
@@ -73,115 +337,193 @@ pub fn init_logger() -> Result<(), String> {
 fn get_log_dir() -> Result<PathBuf, String> {
     let app_data = dirs::data_dir()
         .ok_or_else(|| "Failed to get app data directory".to_string())?;
-    
+
     Ok(app_data.join("com.nexus.account-manager").join("logs"))
 }
 
-/// 截断长字符串
-fn truncate_message(message: &str) -> String {
-    if message.len() <= MAX_LOG_LENGTH {
-        message.to_string()
-    } else {
-        format!("{}... [truncated, total {} chars]", &message[..MAX_LOG_LENGTH], message.len())
+/// 由源文件路径得到模块路径：src/core/storage.rs -> nexus_account_manager_lib::core::storage
+fn module_of(file: &str) -> String {
+    let path = file.replace('\\', "/");
+    let path = path.strip_prefix("src/").unwrap_or(&path);
+    let path = path.strip_suffix(".rs").unwrap_or(path);
+    let path = path.strip_suffix("/mod").unwrap_or(path);
+    match path {
+        "lib" | "main" => CRATE_TARGET.to_string(),
+        _ => format!("{}::{}", CRATE_TARGET, path.replace('/', "::")),
     }
 }
 
-/// 获取日志级别对应的颜色
-fn get_level_color(level: &str) -> &'static str {
-    match level {
-        "INFO" => Colors::INFO,
-        "WARN" => Colors::WARN,
-        "ERROR" => Colors::ERROR,
-        "DEBUG" => Colors::DEBUG,
-        _ => Colors::RESET,
+/// 补全本 crate 的模块名：core::storage -> nexus_account_manager_lib::core::storage
+fn normalize_module(module: &str) -> String {
+    let first = module.split("::").next().unwrap_or_default();
+    if CRATE_MODULES.contains(&first) {
+        format!("{}::{}", CRATE_TARGET, module)
+    } else {
+        module.to_string()
     }
 }
 
-/// 检查并轮转日志文件
-fn check_and_rotate_log(path: &PathBuf) {
-    if let Ok(metadata) = fs::metadata(path) {
-        if metadata.len() > MAX_LOG_FILE_SIZE {
-            // 轮转日志：app.log -> app.log.1
-            let rotated_path = path.with_extension("log.1");
-            let _ = fs::rename(path, rotated_path);
-        }
+/// 写入日志（同时输出到控制台和文件），按调用处所在模块过滤
+#[track_caller]
+fn log_message<T: Display>(level: Level, message: T) {
+    let module = module_of(Location::caller().file());
+    let enabled = LEVELS.read().map(|levels| levels.level_for(&module) >= level).unwrap_or(true);
+    if !enabled {
+        return;
     }
-}
 
-/// 写入日志到文件
-fn write_to_file(path: &PathBuf, level: &str, message: &str) {
-    // 检查文件大小并轮转
-    check_and_rotate_log(path);
-    
-    if let Ok(mut file) = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(path)
-    {
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-        let truncated = truncate_message(message);
-        let _ = writeln!(file, "[{}] [{}] {}", timestamp, level, truncated);
-    }
-}
-
-/// 写入日志（同时输出到控制台和文件）
-fn log_message<T: Display>(level: &str, message: T, to_stderr: bool) {
-    let msg = message.to_string();
-    let truncated = truncate_message(&msg);
-    let timestamp = Local::now().format("%H:%M:%S");
-    
-    // 获取颜色
-    let level_color = get_level_color(level);
-    
-    // 格式化带颜色的控制台输出
-    let colored_output = format!(
-        "{}[{}]{} {}{:<5}{} {}",
-        Colors::TIMESTAMP,
-        timestamp,
-        Colors::RESET,
-        level_color,
-        level,
-        Colors::RESET,
-        truncated
-    );
-    
-    // 输出到控制台
-    if to_stderr {
-        eprintln!("{}", colored_output);
-    } else {
-        println!("{}", colored_output);
-    }
-    
-    // 输出到文件（不带颜色）
-    if let Ok(path_guard) = LOG_FILE_PATH.lock() {
-        if let Some(log_file) = path_guard.as_ref() {
-            write_to_file(log_file, level, &msg);
-        }
+    let msg = redact_text(&message.to_string());
+    let module = module.strip_prefix(CRATE_TARGET).unwrap_or(&module).trim_start_matches("::");
+    match level {
+        Level::ERROR => tracing::event!(target: LOG_TARGET, Level::ERROR, module, "{}", msg),
+        Level::WARN => tracing::event!(target: LOG_TARGET, Level::WARN, module, "{}", msg),
+        Level::INFO => tracing::event!(target: LOG_TARGET, Level::INFO, module, "{}", msg),
+        Level::DEBUG => tracing::event!(target: LOG_TARGET, Level::DEBUG, module, "{}", msg),
+        Level::TRACE => tracing::event!(target: LOG_TARGET, Level::TRACE, module, "{}", msg),
     }
 }
 
 /// 输出信息日志
+#[track_caller]
 pub fn log_info<T: Display>(message: T) {
-    log_message("INFO", message, false);
+    log_message(Level::INFO, message);
 }
 
 /// 输出警告日志
+#[track_caller]
 pub fn log_warn<T: Display>(message: T) {
-    log_message("WARN", message, false);
+    log_message(Level::WARN, message);
 }
 
 /// 输出错误日志
+#[track_caller]
 pub fn log_error<T: Display>(message: T) {
-    log_message("ERROR", message, true);
+    log_message(Level::ERROR, message);
 }
 
 /// 输出调试日志
 #[allow(dead_code)]
+#[track_caller]
 pub fn log_debug<T: Display>(message: T) {
-    log_message("DEBUG", message, false);
+    log_message(Level::DEBUG, message);
+}
+
+/// 修改日志级别：module 为空时修改默认级别，level 为 None 时取消该模块的单独设置。
+/// 返回修改后的全部设置（键为空字符串的是默认级别）
+pub fn set_log_level(module: &str, level: Option<&str>) -> Result<BTreeMap<String, String>, String> {
+    let level = level
+        .map(|level| level.parse::<LevelFilter>().map_err(|_| format!("Invalid log level: {}", level)))
+        .transpose()?;
+
+    let mut levels = LEVELS.write().map_err(|e| e.to_string())?;
+    let mut updated = levels.clone();
+    match (module.trim(), level) {
+        ("", Some(level)) => updated.default = level,
+        ("", None) => updated.default = LevelFilter::INFO,
+        (module, Some(level)) => {
+            updated.modules.insert(normalize_module(module), level);
+        }
+        (module, None) => {
+            updated.modules.remove(&normalize_module(module));
+        }
+    }
+
+    if let Some(handle) = FILTER.get() {
+        handle.reload(updated.env_filter()?)
+            .map_err(|e| format!("Failed to update log filter: {}", e))?;
+    }
+    *levels = updated;
+    Ok(levels.to_map())
+}
+
+/// Tauri 命令的 span：从参数中取平台（platform、account.platform）
+/// 和账户 id（accountId、id、account.id）
+pub fn command_span(invoke: &tauri::ipc::Invoke) -> Span {
+    let span = tracing::info_span!(
+        target: LOG_TARGET,
+        "command",
+        command = invoke.message.command(),
+        platform = Empty,
+        account_id = Empty,
+    );
+    if let tauri::ipc::InvokeBody::Json(args) = invoke.message.payload() {
+        let account = args.get("account");
+        let field = |value: Option<&Value>| value.and_then(Value::as_str).map(str::to_string);
+
+        if let Some(platform) = field(args.get("platform")).or_else(|| field(account.and_then(|a| a.get("platform")))) {
+            span.record("platform", platform.as_str());
+        }
+        let account_id = field(args.get("accountId"))
+            .or_else(|| field(args.get("id")))
+            .or_else(|| field(account.and_then(|a| a.get("id"))));
+        if let Some(account_id) = account_id {
+            span.record("account_id", account_id.as_str());
+        }
+    }
+    span
 }
 
 /// 获取日志文件路径（用于前端查询）
 pub fn get_log_file_path() -> Option<PathBuf> {
     LOG_FILE_PATH.lock().ok()?.clone()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_of() {
+        assert_eq!(module_of("src/core/storage.rs"), format!("{}::core::storage", CRATE_TARGET));
+        assert_eq!(module_of("src\\commands\\mod.rs"), format!("{}::commands", CRATE_TARGET));
+        assert_eq!(module_of("src/lib.rs"), CRATE_TARGET);
+        assert_eq!(normalize_module("core::vault"), format!("{}::core::vault", CRATE_TARGET));
+        assert_eq!(normalize_module("reqwest"), "reqwest");
+    }
+
+    #[test]
+    fn test_level_for_longest_prefix() {
+        let mut levels = LogLevels::default();
+        levels.modules.insert(format!("{}::core", CRATE_TARGET), LevelFilter::WARN);
+        levels.modules.insert(format!("{}::core::storage", CRATE_TARGET), LevelFilter::DEBUG);
+
+        assert_eq!(levels.level_for(&format!("{}::core::storage", CRATE_TARGET)), LevelFilter::DEBUG);
+        assert_eq!(levels.level_for(&format!("{}::core::vault", CRATE_TARGET)), LevelFilter::WARN);
+        // 只按完整的模块名匹配
+        assert_eq!(levels.level_for(&format!("{}::core::storage_x", CRATE_TARGET)), LevelFilter::WARN);
+        assert_eq!(levels.level_for(&format!("{}::commands", CRATE_TARGET)), LevelFilter::INFO);
+        assert!(levels.env_filter().is_ok());
+    }
+
+    #[test]
+    fn test_rotate_generations() {
+        let dir = crate::core::test_support::temp_dir("logs");
+        let path = dir.join("app.log");
+        let read_gz = |n: usize| {
+            let mut content = String::new();
+            let file = File::open(generation_path(&path, n, true)).unwrap();
+            io::Read::read_to_string(&mut flate2::read::GzDecoder::new(file), &mut content).unwrap();
+            content
+        };
+
+        let plain = LogRotation { max_files: 2, compress: false, ..LogRotation::default() };
+        fs::write(&path, "a").unwrap();
+        rotate(&path, &plain).unwrap();
+        assert_eq!(fs::read_to_string(generation_path(&path, 1, false)).unwrap(), "a");
+
+        let compressed = LogRotation { max_files: 2, ..LogRotation::default() };
+        fs::write(&path, "b").unwrap();
+        rotate(&path, &compressed).unwrap();
+        fs::write(&path, "c").unwrap();
+        rotate(&path, &compressed).unwrap();
+
+        // 超出份数的最旧日志被删除，未压缩的旧日志保持原样
+        let generations: Vec<usize> = log_generations(&path).into_iter().map(|(n, _)| n).collect();
+        assert_eq!(generations, vec![1, 2]);
+        assert_eq!(read_gz(1), "c");
+        assert_eq!(read_gz(2), "b");
+        assert!(!path.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -6,6 +6,9 @@ pub mod paths;
 pub mod process;
 pub mod db_inject;
 pub mod logger;
-pub mod config;
+pub mod log_query;
 pub mod http;
 pub mod common;
+pub mod atomic_file;
+pub mod redact;
+pub mod file_lock;
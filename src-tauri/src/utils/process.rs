@@ -12,7 +12,7 @@ use sysinfo::System;
 use std::os::windows::process::CommandExt;
 
 use super::logger::{log_info, log_warn, log_error};
-use super::config::load_app_config;
+use crate::core::settings;
 
 /// 获取当前运行可执行文件的规范化路径
 fn get_current_exe_path() -> Option<std::path::PathBuf> {
@@ -30,9 +30,8 @@ pub fn is_antigravity_running() -> bool {
     let current_pid = std::process::id();
 
     // 加载手动配置路径（移到循环外以提高性能）
-    let manual_path = load_app_config()
-        .ok()
-        .and_then(|c| c.antigravity_executable)
+    let manual_path = settings::load()
+        .antigravity_executable
         .and_then(|p| std::path::PathBuf::from(p).canonicalize().ok());
 
     for (pid, process) in system.processes() {
@@ -211,9 +210,8 @@ fn get_antigravity_pids() -> Vec<u32> {
     let current_exe = get_current_exe_path();
 
     // 加载手动配置路径作为辅助参考
-    let manual_path = load_app_config()
-        .ok()
-        .and_then(|c| c.antigravity_executable)
+    let manual_path = settings::load()
+        .antigravity_executable
         .and_then(|p| std::path::PathBuf::from(p).canonicalize().ok());
 
     for (pid, process) in system.processes() {
@@ -579,7 +577,7 @@ pub fn close_antigravity(#[allow(unused_variables)] timeout_secs: u64) -> Result
             let path_str = path.to_string_lossy().to_string();
             log_info(&format!("Saving Antigravity path before closing: {}", path_str));
             
-            if let Err(e) = super::config::update_antigravity_path(path_str) {
+            if let Err(e) = settings::update(|s| s.antigravity_executable = Some(path_str)) {
                 log_warn(&format!("Failed to save Antigravity path to config: {}", e));
             } else {
                 log_info("Antigravity path saved to config successfully");
@@ -652,9 +650,8 @@ pub fn close_antigravity(#[allow(unused_variables)] timeout_secs: u64) -> Result
             let mut main_pid = None;
 
             // 加载手动配置路径作为最高优先级参考
-            let manual_path = load_app_config()
-                .ok()
-                .and_then(|c| c.antigravity_executable)
+            let manual_path = settings::load()
+                .antigravity_executable
                 .and_then(|p| std::path::PathBuf::from(p).canonicalize().ok());
 
             log_info("Analyzing process list to identify main process:");
@@ -828,9 +825,8 @@ pub fn close_antigravity(#[allow(unused_variables)] timeout_secs: u64) -> Result
             let mut main_pid = None;
 
             // 加载手动配置路径作为最高优先级参考
-            let manual_path = load_app_config()
-                .ok()
-                .and_then(|c| c.antigravity_executable)
+            let manual_path = settings::load()
+                .antigravity_executable
                 .and_then(|p| std::path::PathBuf::from(p).canonicalize().ok());
 
             log_info("Analyzing Linux process list to identify main process:");
@@ -966,11 +962,9 @@ pub fn start_antigravity() -> Result<(), String> {
     log_info("Starting Antigravity...");
 
     // 优先使用配置中手动指定的路径和参数
-    let config = load_app_config().ok();
-    let manual_path = config
-        .as_ref()
-        .and_then(|c| c.antigravity_executable.clone());
-    let args = config.and_then(|c| c.antigravity_args.clone());
+    let config = settings::load();
+    let manual_path = config.antigravity_executable;
+    let args = config.antigravity_args;
 
     if let Some(mut path_str) = manual_path {
         let mut path = std::path::PathBuf::from(&path_str);
@@ -1146,7 +1140,7 @@ pub fn start_antigravity() -> Result<(), String> {
         let path_str = path.to_string_lossy().to_string();
         log_info(&format!("Detected Antigravity path after startup: {}", path_str));
         
-        if let Err(e) = super::config::update_antigravity_path(path_str) {
+        if let Err(e) = settings::update(|s| s.antigravity_executable = Some(path_str)) {
             log_warn(&format!("Failed to save detected path to config: {}", e));
         } else {
             log_info("Antigravity path saved to config after startup");
@@ -9,6 +9,7 @@ import { ThemeManager } from './components/common/ThemeManager'
 import { useEffect } from 'react'
 import { usePlatformStore } from './stores/usePlatformStore'
 import { Toaster } from "@/components/ui/sonner"
+import { listen } from '@tauri-apps/api/event'
 
 function App() {
   const loadAllAccounts = usePlatformStore((state) => state.loadAllAccounts)
@@ -20,6 +21,19 @@ function App() {
     })
   }, [loadAllAccounts])
 
+  useEffect(() => {
+    // 存储文件被其他设备同步修改、或切换了存储库后重新加载
+    const reload = () => {
+      loadAllAccounts().catch((error) => {
+        logError('Failed to reload accounts:', error)
+      })
+    }
+    const unlisteners = [listen('storage-changed', reload), listen('vault-opened', reload)]
+    return () => {
+      unlisteners.forEach((unlisten) => unlisten.then((fn) => fn()))
+    }
+  }, [loadAllAccounts])
+
   return (
     <>
       <ThemeManager />
@@ -0,0 +1,59 @@
+/**
+ * 目标存储位置已有数据时的处理选择对话框
+ * 可改用目标位置的数据、用当前数据覆盖，或将当前数据合并进去
+ */
+
+import {
+  AlertDialog,
+  AlertDialogCancel,
+  AlertDialogContent,
+  AlertDialogDescription,
+  AlertDialogFooter,
+  AlertDialogHeader,
+  AlertDialogTitle,
+} from '@/components/ui/alert-dialog'
+import type { ExistingStoreAction } from '@/services/StorageService'
+import { useTranslation } from 'react-i18next'
+
+interface StorageExistsDialogProps {
+  open: boolean
+  onOpenChange: (open: boolean) => void
+  onChoose: (action: ExistingStoreAction) => void | Promise<void>
+}
+
+const ACTIONS: ExistingStoreAction[] = ['merge', 'adopt', 'overwrite']
+
+export function StorageExistsDialog({ open, onOpenChange, onChoose }: StorageExistsDialogProps) {
+  const { t } = useTranslation()
+
+  const handleChoose = async (action: ExistingStoreAction) => {
+    onOpenChange(false)
+    await onChoose(action)
+  }
+
+  return (
+    <AlertDialog open={open} onOpenChange={onOpenChange}>
+      <AlertDialogContent>
+        <AlertDialogHeader>
+          <AlertDialogTitle>{t('settings.storageExists.title')}</AlertDialogTitle>
+          <AlertDialogDescription>{t('settings.storageExists.description')}</AlertDialogDescription>
+        </AlertDialogHeader>
+        <div className="space-y-2">
+          {ACTIONS.map((action) => (
+            <button
+              key={action}
+              onClick={() => handleChoose(action)}
+              className="w-full text-left p-3 rounded-lg border border-border hover:bg-muted transition-colors"
+            >
+              <p className="text-sm font-medium">{t(`settings.storageExists.${action}`)}</p>
+              <p className="text-xs text-muted-foreground">{t(`settings.storageExists.${action}Desc`)}</p>
+            </button>
+          ))}
+        </div>
+        <AlertDialogFooter>
+          <AlertDialogCancel>{t('common.cancel')}</AlertDialogCancel>
+        </AlertDialogFooter>
+      </AlertDialogContent>
+    </AlertDialog>
+  )
+}
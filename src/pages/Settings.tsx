@@ -5,7 +5,8 @@ import { useTheme } from '@/hooks/useTheme'
 import { useState, useEffect } from 'react'
 import { useTranslation } from 'react-i18next'
 import { cn } from '@/lib/utils'
-import { StorageService } from '@/services/StorageService'
+import { StorageService, type ExistingStoreAction } from '@/services/StorageService'
+import { StorageExistsDialog } from '@/components/dialogs/StorageExistsDialog'
 import {
   FolderOpen,
   Save,
@@ -27,6 +28,8 @@ export function Settings() {
   const [storagePath, setStoragePath] = useState('')
   const [loading, setLoading] = useState(false)
   const [success, setSuccess] = useState(false)
+  const [moveFiles, setMoveFiles] = useState(false)
+  const [existsOpen, setExistsOpen] = useState(false)
   const storageService = StorageService.getInstance()
 
   useEffect(() => {
@@ -53,11 +56,17 @@ export function Settings() {
     }
   }
 
-  const handleUpdatePath = async () => {
+  const handleUpdatePath = async (existing?: ExistingStoreAction) => {
     if (!storagePath) return
     setLoading(true)
     try {
-      await storageService.setStoragePath(storagePath)
+      try {
+        await storageService.setStoragePath(storagePath, { existing, moveFiles })
+      } catch (error) {
+        if ((error as { kind?: string })?.kind !== 'storage_exists') throw error
+        setExistsOpen(true)
+        return
+      }
       setSuccess(true)
       setTimeout(() => setSuccess(false), 3000)
     } catch (error) {
@@ -141,7 +150,7 @@ export function Settings() {
                 {t('settings.reset')}
               </Button>
               <Button
-                onClick={handleUpdatePath}
+                onClick={() => handleUpdatePath()}
                 disabled={loading}
                 className={cn(
                   "min-w-[100px] transition-all",
@@ -163,6 +172,18 @@ export function Settings() {
                 )}
               </Button>
             </div>
+            <label className="flex items-center gap-3 pl-1 cursor-pointer">
+              <input
+                type="checkbox"
+                checked={moveFiles}
+                onChange={(e) => setMoveFiles(e.target.checked)}
+                className="w-4 h-4 rounded accent-primary"
+              />
+              <div>
+                <p className="text-sm font-medium">{t('settings.moveFiles')}</p>
+                <p className="text-xs text-muted-foreground">{t('settings.moveFilesDesc')}</p>
+              </div>
+            </label>
             <p className="text-xs text-muted-foreground pl-1 flex items-start gap-2">
               <Info className="h-3 w-3 mt-0.5 flex-shrink-0" />
               <span>
@@ -190,6 +211,12 @@ export function Settings() {
         </CardContent>
       </Card>
 
+      <StorageExistsDialog
+        open={existsOpen}
+        onOpenChange={setExistsOpen}
+        onChoose={handleUpdatePath}
+      />
+
       {/* Appearance Settings */}
       <Card className="border-border bg-card shadow-sm">
         <CardHeader className="border-b border-border pb-4">
@@ -19,7 +19,7 @@ export class AntigravityAccountService {
    * 4. 关闭 Antigravity IDE
    * 5. 注入 Token 到数据库
    * 6. 重启 Antigravity IDE
-   * 7. 批量更新账号状态（同平台只有一个活跃）
+   * 7. 后端标记激活账号（同平台只有一个活跃）并保存新 Token，前端重新加载
    */
   static async switchAccount(accountId: string): Promise<void> {
     const store = usePlatformStore.getState()
@@ -33,11 +33,8 @@ export class AntigravityAccountService {
     logInfo(`[Switch] Starting switch to: ${targetAccount.email}`)
 
     // 1. 调用后端切换命令（包含进程控制和数据库注入）
-    const tokenResponse = await invoke<{
-      access_token: string
-      expires_in: number
-      token_type: string
-    }>('antigravity_switch_account', {
+    // 后端会刷新 Token、标记激活账号并保存
+    await invoke('antigravity_switch_account', {
       accountId: targetAccount.id,
       refreshToken: targetAccount.token.refresh_token,
       email: targetAccount.email,
@@ -56,36 +53,8 @@ export class AntigravityAccountService {
       logInfo(`[Switch] Generated and bound new machine ID for account: ${targetAccount.email}`)
     }
 
-    // 3. 准备更新数据
-    const now = Date.now()
-    const expiryTimestamp = now + (tokenResponse.expires_in * 1000)
-
-    // 4. 批量更新：先将所有同平台账号设为非活跃，再激活目标账号
-    const antigravityAccounts = accounts.filter(a => a.platform === 'antigravity')
-    
-    logInfo(`[Switch] Updating ${antigravityAccounts.length} Antigravity accounts`)
-    
-    // 先将所有账号设为非活跃
-    for (const acc of antigravityAccounts) {
-      if (acc.id !== accountId && acc.isActive) {
-        await store.updateAccount(acc.id, {
-          isActive: false
-        })
-        logInfo(`[Switch] Deactivated: ${acc.email}`)
-      }
-    }
-
-    // 然后激活目标账号并更新 Token
-    await store.updateAccount(accountId, {
-      isActive: true,
-      lastUsedAt: now,
-      token: {
-        ...targetAccount.token,
-        access_token: tokenResponse.access_token,
-        expires_in: tokenResponse.expires_in,
-        expiry_timestamp: expiryTimestamp,
-      }
-    })
+    // 3. 后端已更新激活状态和 Token，重新加载账户列表
+    await store.loadAllAccounts()
 
     logInfo(`[Switch] Activated: ${targetAccount.email}`)
     logInfo(`[Switch] Switch completed successfully`)
@@ -126,7 +95,6 @@ export class AntigravityAccountService {
       },
       quota: quotaData,
       is_forbidden: quotaData?.is_forbidden || false,
-      lastUsedAt: now
     })
   }
 
@@ -45,7 +45,6 @@ export function EditAccountDialog({ account, open, onClose }: EditAccountDialogP
             email: updatedAccount.email,
             name: updatedAccount.name,
             config: (updatedAccount as ClaudeAccount).config,
-            lastUsedAt: Date.now()
         })
         
         await loadAllAccounts()
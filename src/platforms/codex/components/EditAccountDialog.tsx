@@ -44,8 +44,7 @@ export function EditAccountDialog({ account, open, onClose }: EditAccountDialogP
         await updateAccount(account.id, {
             email: updatedAccount.email,
             name: updatedAccount.name,
-            config: (updatedAccount as CodexAccount).config,
-            lastUsedAt: Date.now()
+            config: (updatedAccount as CodexAccount).config
         })
         
         await loadAllAccounts()
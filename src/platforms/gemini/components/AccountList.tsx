@@ -21,7 +21,7 @@ type ViewMode = 'grid' | 'list'
 export function GeminiAccountList() {
   const { t } = useTranslation()
   const accounts = usePlatformStore((state) => state.accounts)
-  const updateAccount = usePlatformStore((state) => state.updateAccount)
+  const loadAllAccounts = usePlatformStore((state) => state.loadAllAccounts)
   const [exportOpen, setExportOpen] = useState(false)
   const [isSwitching, setIsSwitching] = useState(false)
   const [editAccount, setEditAccount] = useState<GeminiAccount | null>(null)
@@ -71,20 +71,10 @@ export function GeminiAccountList() {
         return
       }
       
-      await invoke('switch_gemini_account', { settings: JSON.stringify(config) })
+      await invoke('switch_gemini_account', { settings: JSON.stringify(config), accountId: account.id })
       
-      // 2. 将其他账户设置为非激活状态
-      const updatePromises = geminiAccounts
-        .filter(acc => acc.id !== account.id && acc.isActive)
-        .map(acc => updateAccount(acc.id, { isActive: false }))
-      
-      await Promise.all(updatePromises)
-      
-      // 3. 更新当前账户为激活状态
-      await updateAccount(account.id, { 
-        isActive: true,
-        lastUsedAt: Date.now()
-      })
+      // 2. 后端已更新激活状态，重新加载账户列表
+      await loadAllAccounts()
       
       toast.success(t('gemini.switchSuccess', 'Gemini account switched successfully'))
     } catch (error: any) {
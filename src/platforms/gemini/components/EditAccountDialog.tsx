@@ -45,7 +45,6 @@ export function EditAccountDialog({ account, open, onClose }: EditAccountDialogP
             email: updatedAccount.email,
             name: updatedAccount.name,
             config: (updatedAccount as GeminiAccount).config, // 更新 config 用于前端显示
-            lastUsedAt: Date.now()
         })
         
         await loadAllAccounts()
@@ -133,7 +133,8 @@ export class KiroAccountService {
                 region: targetAccount.credentials.region,
                 startUrl: undefined, // Use default
                 authMethod: targetAccount.idp === 'BuilderId' ? 'IdC' : 'social',
-                provider: targetAccount.idp
+                provider: targetAccount.idp,
+                accountId
             })
             logInfo(`[Kiro Switch] Credentials written to AWS SSO cache`)
         } catch (e) {
@@ -141,27 +142,8 @@ export class KiroAccountService {
             throw new Error(`切换账号失败: ${e}`)
         }
 
-        // 4. Batch update: deactivate all Kiro accounts, then activate target
-        const kiroAccounts = accounts.filter(a => a.platform === 'kiro')
-        
-        logInfo(`[Kiro Switch] Updating ${kiroAccounts.length} Kiro accounts`)
-        
-        // Deactivate all accounts first
-        for (const acc of kiroAccounts) {
-            if (acc.id !== accountId && acc.isActive) {
-                await store.updateAccount(acc.id, {
-                    isActive: false
-                })
-                logInfo(`[Kiro Switch] Deactivated: ${acc.email}`)
-            }
-        }
-
-        // Activate target account
-        const now = Date.now()
-        await store.updateAccount(accountId, {
-            isActive: true,
-            lastUsedAt: now
-        })
+        // 4. Backend has marked the account active, reload the account list
+        await store.loadAllAccounts()
 
         logInfo(`[Kiro Switch] Activated: ${targetAccount.email}`)
         logInfo(`[Kiro Switch] Switch completed successfully`)
@@ -240,7 +222,6 @@ export class KiroAccountService {
                 },
                 email: quotaResult.email || account.email,
                 userId: quotaResult.userId,
-                lastUsedAt: now
             })
 
             return {
@@ -1,5 +1,14 @@
 import { invoke } from '@tauri-apps/api/core'
 
+/** 目标位置已有存储时的处理方式 */
+export type ExistingStoreAction = 'adopt' | 'overwrite' | 'merge'
+
+export interface SetStoragePathOptions {
+  existing?: ExistingStoreAction
+  /** 一并移动旧位置的备份、快照和审计日志 */
+  moveFiles?: boolean
+}
+
 /**
  * 存储服务
  * 统一管理数据持久化路径和操作
@@ -29,9 +38,10 @@ export class StorageService {
 
   /**
    * 设置存储路径
+   * 目标位置已有数据且未指定 existing 时，后端返回 kind 为 storage_exists 的错误
    */
-  async setStoragePath(path: string): Promise<void> {
-    await invoke('set_storage_path', { path })
+  async setStoragePath(path: string, options: SetStoragePathOptions = {}): Promise<void> {
+    await invoke('set_storage_path', { path, existing: options.existing, moveFiles: options.moveFiles })
     this.currentPath = path
   }
 
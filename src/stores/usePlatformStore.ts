@@ -20,6 +20,10 @@ interface BackendAccount {
   is_active: boolean
   last_used_at: number
   created_at: number
+  tags?: string[]
+  group?: string | null
+  notes?: string | null
+  custom_fields?: Record<string, string>
   platform_data: any
 }
 
@@ -57,7 +61,7 @@ interface PlatformStore {
 // Helper: Transform Frontend -> Backend
 const toBackend = (account: Account): BackendAccount => {
   // Extract common fields
-  const { id, platform, name, email, avatar, isActive, lastUsedAt, createdAt, ...rest } = account
+  const { id, platform, name, email, avatar, isActive, lastUsedAt, createdAt, tags, group, notes, customFields, ...rest } = account
 
   return {
     id,
@@ -68,13 +72,17 @@ const toBackend = (account: Account): BackendAccount => {
     is_active: isActive,
     last_used_at: lastUsedAt,
     created_at: createdAt,
+    tags: tags || [],
+    group: group || null,
+    notes: notes || null,
+    custom_fields: customFields || {},
     platform_data: rest // Store remaining platform-specific fields in platform_data
   }
 }
 
 // Helper: Transform Backend -> Frontend
 const toFrontend = (backend: BackendAccount): Account => {
-  const { id, platform, name, email, avatar, is_active, last_used_at, created_at, platform_data } = backend
+  const { id, platform, name, email, avatar, is_active, last_used_at, created_at, tags, group, notes, custom_fields, platform_data } = backend
 
   const common = {
     id,
@@ -85,6 +93,10 @@ const toFrontend = (backend: BackendAccount): Account => {
     isActive: is_active,
     lastUsedAt: last_used_at, // Provide default if missing? No, backend guarantees i64.
     createdAt: created_at,
+    tags: tags || [],
+    group: group || undefined,
+    notes: notes || undefined,
+    customFields: custom_fields || {},
   }
 
   // Merge common fields with platform data
@@ -150,10 +162,11 @@ export const usePlatformStore = create<PlatformStore>((set, get) => ({
       }
 
       const backendAccount = toBackend(updated)
-      await invoke('update_account', { id, account: backendAccount })
+      // 使用后端返回的账户：激活状态和最近使用时间只由切换命令修改，前端传入的值会被忽略
+      const saved = toFrontend(await invoke<BackendAccount>('update_account', { id, account: backendAccount }))
 
       set((state) => ({
-        accounts: state.accounts.map((acc) => (acc.id === id ? updated : acc)),
+        accounts: state.accounts.map((acc) => (acc.id === id ? saved : acc)),
         isLoading: false
       }))
     } catch (err: any) {
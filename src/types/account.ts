@@ -10,6 +10,10 @@ export interface BaseAccount {
     isActive: boolean;
     lastUsedAt: number;
     createdAt: number;
+    tags?: string[];
+    group?: string;
+    notes?: string;
+    customFields?: Record<string, string>;
 }
 
 // --- Antigravity Specifics ---